@@ -0,0 +1,207 @@
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+///
+/// Adapter for writing JSON to a [`tokio::io::AsyncWrite`], e.g. a socket.
+///
+/// Requires the `tokio` feature.
+///
+/// Output is collected in a buffer, because [`std::fmt::Write`] can not wait for the socket.
+/// Call [`AsyncIoWriter::flush_if_full`] between values to write the buffer to the socket once it
+/// reached the flush threshold. Waiting for the socket there limits the memory used for large documents.
+///
+/// Flushing is cancellation safe: if a flush is cancelled, e.g. by a timeout, only the bytes that
+/// were not written yet stay in the buffer and are written by the next flush.
+///
+/// After an error the output is incomplete and the writer should not be used anymore.
+///
+/// ```
+/// use json_writer::{write_array, AsyncIoWriter};
+/// use tokio::io::AsyncWrite;
+///
+/// async fn write_numbers<W: AsyncWrite + Unpin>(socket: W) -> std::io::Result<()> {
+///     let mut writer = AsyncIoWriter::new(socket).flush_threshold(4096);
+///     let mut array = write_array(&mut writer).unwrap();
+///     for i in 0..1000000u32 {
+///         array.value(i).unwrap();
+///         array.writer_mut().flush_if_full().await?;
+///     }
+///     array.end().unwrap();
+///     writer.flush().await
+/// }
+/// ```
+///
+pub struct AsyncIoWriter<W: AsyncWrite + Unpin> {
+    writer: W,
+    /// Bytes are removed once written, which might split characters.
+    buffer: Vec<u8>,
+    flush_threshold: usize,
+    bytes_written: usize,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncIoWriter<W> {
+    ///
+    /// Creates a new AsyncIoWriter that writes to `writer` with a flush threshold of 8 KiB.
+    ///
+    pub fn new(writer: W) -> AsyncIoWriter<W> {
+        AsyncIoWriter {
+            writer,
+            buffer: Vec::new(),
+            flush_threshold: 8192,
+            bytes_written: 0,
+        }
+    }
+
+    ///
+    /// Sets the number of buffered bytes at which [`AsyncIoWriter::flush_if_full`] writes the buffer.
+    ///
+    pub fn flush_threshold(mut self, bytes: usize) -> AsyncIoWriter<W> {
+        self.flush_threshold = bytes;
+        self
+    }
+
+    ///
+    /// Returns the number of bytes in the buffer that were not written yet.
+    ///
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    ///
+    /// Returns the number of bytes written to the encapsulated writer.
+    ///
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    ///
+    /// Writes the buffer to the encapsulated writer if it has reached the flush threshold.
+    ///
+    pub async fn flush_if_full(&mut self) -> std::io::Result<()> {
+        if self.buffer.len() >= self.flush_threshold {
+            self.write_buffer().await?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Writes the buffer to the encapsulated writer and flushes it.
+    ///
+    pub async fn flush(&mut self) -> std::io::Result<()> {
+        self.write_buffer().await?;
+        self.writer.flush().await
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated writer.
+    ///
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated writer.
+    ///
+    /// Anything written directly to the encapsulated writer is not counted.
+    ///
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    ///
+    /// Consumes this writer and returns the encapsulated writer.
+    ///
+    /// Output that was not flushed is discarded.
+    ///
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    async fn write_buffer(&mut self) -> std::io::Result<()> {
+        // unlike write_all, a single write does not lose track of written bytes when cancelled
+        while !self.buffer.is_empty() {
+            let written = self.writer.write(&self.buffer).await?;
+            if written == 0 {
+                return Err(std::io::ErrorKind::WriteZero.into());
+            }
+            self.bytes_written += written;
+            self.buffer.drain(..written);
+        }
+        Ok(())
+    }
+}
+
+impl<W: AsyncWrite + Unpin> std::fmt::Write for AsyncIoWriter<W> {
+    #[inline(always)]
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.buffer.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::write_array;
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn test_duplex() -> std::io::Result<()> {
+        let (client, mut server) = tokio::io::duplex(64);
+
+        let write = async move {
+            let mut writer = AsyncIoWriter::new(client).flush_threshold(32);
+            let mut array = write_array(&mut writer).unwrap();
+            for i in 0..1000u32 {
+                array.value(i).unwrap();
+                array.writer_mut().flush_if_full().await?;
+                assert!(array.writer().buffered_len() < 32);
+            }
+            array.end().unwrap();
+            writer.flush().await?;
+            assert_eq!(writer.buffered_len(), 0);
+            Ok::<usize, std::io::Error>(writer.bytes_written())
+        };
+        let read = async move {
+            let mut json = String::new();
+            server.read_to_string(&mut json).await?;
+            Ok::<String, std::io::Error>(json)
+        };
+
+        let (bytes_written, json) = tokio::try_join!(write, read)?;
+        let numbers: Vec<u32> = (0..1000).collect();
+        assert_eq!(json, crate::to_json_string(&numbers));
+        assert_eq!(bytes_written, json.len());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_flush() -> std::io::Result<()> {
+        let (client, mut server) = tokio::io::duplex(16);
+        let numbers: Vec<u32> = (0..100).collect();
+        let mut writer = AsyncIoWriter::new(client);
+        crate::write_value(&mut writer, &numbers).unwrap();
+
+        // the flush fills the pipe and is cancelled while waiting for the reader
+        tokio::select! {
+            biased;
+            _ = writer.flush() => panic!("flush should wait for the reader"),
+            _ = std::future::ready(()) => {}
+        }
+        assert_eq!(writer.bytes_written(), 16);
+        assert_eq!(writer.buffered_len(), crate::json_len(&numbers) - 16);
+
+        let write = async move {
+            writer.flush().await?;
+            drop(writer);
+            Ok::<(), std::io::Error>(())
+        };
+        let read = async move {
+            let mut json = String::new();
+            server.read_to_string(&mut json).await?;
+            Ok::<String, std::io::Error>(json)
+        };
+        let ((), json) = tokio::try_join!(write, read)?;
+        assert_eq!(json, crate::to_json_string(&numbers));
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+use crate::scan::{is_scalar_byte, Container, Event, Scanner};
+use alloc::string::String;
+
+/// Member appended to the top-level object when the output was truncated.
+const TRUNCATION_MARKER: &str = "\"_truncated\":true";
+
+///
+/// Writer that never forwards more than a fixed number of bytes and keeps the output valid JSON.
+///
+/// Room for closing all open strings, arrays and objects is reserved while writing.
+/// When the budget runs out, the member being written is dropped, strings are cut at a
+/// character boundary and all open containers are closed.
+/// Optionally `"_truncated":true` is appended to the top-level object.
+///
+/// The write that exhausted the budget and all following writes return [`core::fmt::Error`],
+/// so the code producing the JSON stops early. Closing brackets written when the
+/// [`crate::JSONObjectWriter`]s and [`crate::JSONArrayWriter`]s are dropped are ignored.
+/// Use [`BudgetWriter::is_truncated`] to distinguish this from errors of the encapsulated writer.
+///
+/// The budget must at least fit the empty top-level container (and the marker, if enabled).
+///
+/// ```
+/// use json_writer::{write_object, BudgetWriter};
+///
+/// let mut writer = BudgetWriter::new(String::new(), 48).mark_truncation(true);
+/// let mut object_writer = write_object(&mut writer).unwrap();
+/// object_writer.member("id", 7u8).unwrap();
+/// let mut values = object_writer.array("values").unwrap();
+/// for i in 0..1000u32 {
+///     if values.value(i).is_err() {
+///         break;
+///     }
+/// }
+/// drop(values);
+/// drop(object_writer);
+///
+/// assert!(writer.is_truncated());
+/// assert_eq!(writer.writer(), "{\"id\":7,\"values\":[0,1,2,3,4],\"_truncated\":true}");
+/// ```
+///
+pub struct BudgetWriter<W: core::fmt::Write> {
+    writer: W,
+    scanner: Scanner,
+    budget: usize,
+    written: usize,
+    /// Comma, key and colon of the next member. Only written together with the member's value.
+    pending: String,
+    mark_truncation: bool,
+    last_written: u8,
+    truncated: bool,
+}
+
+impl<W: core::fmt::Write> BudgetWriter<W> {
+    ///
+    /// Creates a new BudgetWriter that forwards at most `budget` bytes to `writer`.
+    ///
+    pub fn new(writer: W, budget: usize) -> BudgetWriter<W> {
+        BudgetWriter {
+            writer,
+            scanner: Scanner::new(),
+            budget,
+            written: 0,
+            pending: String::new(),
+            mark_truncation: false,
+            last_written: 0,
+            truncated: false,
+        }
+    }
+
+    ///
+    /// Enables or disables appending `"_truncated":true` to the top-level object on truncation.
+    ///
+    pub fn mark_truncation(mut self, enabled: bool) -> BudgetWriter<W> {
+        self.mark_truncation = enabled;
+        self
+    }
+
+    ///
+    /// Returns true if the output was truncated because the budget ran out.
+    ///
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    ///
+    /// Returns the number of bytes forwarded to the encapsulated writer.
+    ///
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated writer.
+    ///
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated writer.
+    ///
+    /// Anything written directly to the encapsulated writer is not accounted for.
+    ///
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    ///
+    /// Consumes this writer and returns the encapsulated writer.
+    ///
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    ///
+    /// Returns the number of bytes that must stay available to close everything that is open.
+    ///
+    fn reserved(&self) -> usize {
+        let stack = self.scanner.stack();
+        let mut reserved = stack.len();
+        if self.scanner.in_string() == Some(false) {
+            reserved += 1;
+        }
+        if self.mark_truncation && stack.first() == Some(&Container::Object) {
+            reserved += 1 + TRUNCATION_MARKER.len();
+        }
+        reserved
+    }
+
+    fn forward(&mut self, s: &str) -> core::fmt::Result {
+        if let Some(&last) = s.as_bytes().last() {
+            self.writer.write_str(s)?;
+            self.written += s.len();
+            self.last_written = last;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Closes everything that was written so far. `event` is the first event of the unit that did not fit.
+    ///
+    fn truncate(&mut self, event: Event) -> core::fmt::Result {
+        self.truncated = true;
+        self.pending.clear();
+
+        let mut closers = String::new();
+        if matches!(
+            event,
+            Event::StringByte { key: false } | Event::StringEnd { key: false }
+        ) {
+            closers.push('"');
+        }
+        let stack = self.scanner.stack();
+        let open = match event {
+            Event::Open(_) => &stack[..stack.len() - 1],
+            _ => stack,
+        };
+        for (depth, container) in open.iter().enumerate().rev() {
+            if depth == 0 && self.mark_truncation && *container == Container::Object {
+                if !closers.is_empty() || self.last_written != b'{' {
+                    closers.push(',');
+                }
+                closers.push_str(TRUNCATION_MARKER);
+            }
+            closers.push(container.closer());
+        }
+        self.forward(&closers)?;
+        Err(core::fmt::Error)
+    }
+}
+
+impl<W: core::fmt::Write> core::fmt::Write for BudgetWriter<W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        if self.truncated {
+            return Err(core::fmt::Error);
+        }
+        let bytes = s.as_bytes();
+        let mut index = 0;
+        while index < bytes.len() {
+            let start = index;
+            let event = self
+                .scanner
+                .push(bytes[index])
+                .map_err(|_| core::fmt::Error)?;
+            index += 1;
+
+            // Numbers, literals, escape sequences and utf-8 characters must not be split.
+            let unit_len = match event {
+                Event::ScalarStart => bytes[index..]
+                    .iter()
+                    .take_while(|b| is_scalar_byte(**b))
+                    .count(),
+                Event::StringByte { .. } if bytes[start] == b'\\' => {
+                    if bytes.get(index) == Some(&b'u') {
+                        5
+                    } else {
+                        1
+                    }
+                }
+                Event::StringByte { .. } => match bytes[start] {
+                    0xF0..=0xFF => 3,
+                    0xE0..=0xEF => 2,
+                    0xC0..=0xDF => 1,
+                    _ => 0,
+                },
+                _ => 0,
+            };
+            let end = usize::min(index + unit_len, bytes.len());
+            while index < end {
+                self.scanner
+                    .push(bytes[index])
+                    .map_err(|_| core::fmt::Error)?;
+                index += 1;
+            }
+            let unit = &s[start..end];
+
+            match event {
+                Event::Comma
+                | Event::Colon
+                | Event::StringStart { key: true }
+                | Event::StringByte { key: true }
+                | Event::StringEnd { key: true } => self.pending.push_str(unit),
+                Event::Whitespace if !self.pending.is_empty() => self.pending.push_str(unit),
+                _ => {
+                    let required = self.pending.len() + unit.len() + self.reserved();
+                    if self.written + required > self.budget {
+                        return self.truncate(event);
+                    }
+                    let pending = core::mem::take(&mut self.pending);
+                    self.forward(&pending)?;
+                    self.pending = pending;
+                    self.pending.clear();
+                    self.forward(unit)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{to_json_string, write_array, write_object, write_value};
+
+    #[test]
+    fn test_fits() -> Result<(), core::fmt::Error> {
+        let numbers: [u32; 4] = [1, 20, 300, 4000];
+        let json = to_json_string(&numbers[..]);
+
+        let mut writer = BudgetWriter::new(String::new(), json.len());
+        write_value(&mut writer, &numbers[..])?;
+        assert!(!writer.is_truncated());
+        assert_eq!(writer.writer(), &json);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncate_array() {
+        for budget in 2..16 {
+            let mut writer = BudgetWriter::new(String::new(), budget);
+            let numbers: [u32; 4] = [1, 20, 300, 4000];
+            let _ = write_value(&mut writer, &numbers[..]);
+            let json = writer.into_writer();
+            assert!(json.len() <= budget, "{}", json);
+            assert!(json.ends_with(']'), "{}", json);
+            assert!(!json.contains(",]"), "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_truncate_string() -> Result<(), core::fmt::Error> {
+        let mut writer = BudgetWriter::new(String::new(), 16);
+        let mut array = write_array(&mut writer)?;
+        assert!(array.value("äöü\n\u{1}abcdef").is_err());
+        drop(array);
+        // neither the 'ü' nor the escape sequence are split
+        assert_eq!(writer.writer(), "[\"äöü\\n\"]");
+
+        let mut writer = BudgetWriter::new(String::new(), 9);
+        let mut array = write_array(&mut writer)?;
+        assert!(array.value("äöü\n\u{1}abcdef").is_err());
+        drop(array);
+        assert_eq!(writer.writer(), "[\"äö\"]");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncate_nested() -> Result<(), core::fmt::Error> {
+        let mut writer = BudgetWriter::new(String::new(), 36).mark_truncation(true);
+        let mut object_writer = write_object(&mut writer)?;
+        let mut nested = object_writer.object("a")?;
+        let mut array = nested.array("b")?;
+        array.value(1u8)?;
+        assert!(array.value(1000u32).is_err());
+        assert!(array.end().is_err());
+        assert!(nested.member("c", 2u8).is_err());
+        drop(nested);
+        drop(object_writer);
+
+        assert!(writer.is_truncated());
+        assert_eq!(writer.writer(), "{\"a\":{\"b\":[1]},\"_truncated\":true}");
+
+        // the marker does not need a comma if nothing else fits
+        let mut writer = BudgetWriter::new(String::new(), 22).mark_truncation(true);
+        let mut object_writer = write_object(&mut writer)?;
+        assert!(object_writer.member("key", "value").is_err());
+        drop(object_writer);
+        assert_eq!(writer.writer(), "{\"_truncated\":true}");
+
+        Ok(())
+    }
+}
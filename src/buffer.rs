@@ -0,0 +1,122 @@
+use crate::JSONWriterValue;
+use bytes::{BufMut, Bytes, BytesMut};
+
+///
+/// Writer that appends to a byte buffer, e.g. a `Vec<u8>` or a [`BytesMut`].
+///
+/// Requires the `bytes` feature.
+///
+/// A [`BytesMut`] can also be passed to [`crate::write_object`] directly,
+/// this writer is required for other [`BufMut`] implementations like `Vec<u8>`.
+///
+/// ```
+/// use json_writer::{write_object, BytesWriter};
+///
+/// let mut writer = BytesWriter::new(Vec::<u8>::new());
+/// let mut object_writer = write_object(&mut writer).unwrap();
+/// object_writer.member("id", 1u8).unwrap();
+/// object_writer.end().unwrap();
+/// assert_eq!(writer.into_writer(), b"{\"id\":1}");
+/// ```
+///
+pub struct BytesWriter<B: BufMut> {
+    writer: B,
+}
+
+impl<B: BufMut> BytesWriter<B> {
+    ///
+    /// Creates a new BytesWriter that appends to `writer`.
+    ///
+    pub fn new(writer: B) -> BytesWriter<B> {
+        BytesWriter { writer }
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated buffer.
+    ///
+    pub fn writer(&self) -> &B {
+        &self.writer
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated buffer.
+    ///
+    pub fn writer_mut(&mut self) -> &mut B {
+        &mut self.writer
+    }
+
+    ///
+    /// Consumes this writer and returns the encapsulated buffer.
+    ///
+    pub fn into_writer(self) -> B {
+        self.writer
+    }
+}
+
+impl BytesWriter<BytesMut> {
+    ///
+    /// Consumes this writer and returns the output as [`Bytes`], without copying it.
+    ///
+    pub fn freeze(self) -> Bytes {
+        self.writer.freeze()
+    }
+}
+
+impl<B: BufMut> core::fmt::Write for BytesWriter<B> {
+    #[inline(always)]
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        if self.writer.remaining_mut() < s.len() {
+            return Err(core::fmt::Error);
+        }
+        self.writer.put_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+///
+/// Serializes the given `value` to JSON as [`Bytes`].
+///
+/// Requires the `bytes` feature.
+///
+/// The output is written to a [`BytesMut`] which is frozen afterwards, so it is not copied.
+///
+/// ```
+/// use json_writer::to_json_bytes;
+///
+/// assert_eq!(to_json_bytes(&[1u8, 2u8][..]), "[1,2]");
+/// ```
+///
+pub fn to_json_bytes<T: JSONWriterValue>(value: T) -> Bytes {
+    let mut result = BytesMut::with_capacity(value.size_hint().0);
+    // BytesMut never returns an error in it's Write implementation.
+    value.write_json(&mut result).unwrap();
+    result.freeze()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{write_array, write_object};
+
+    #[test]
+    fn test_bytes() -> Result<(), core::fmt::Error> {
+        let mut buffer = BytesMut::new();
+        let mut object_writer = write_object(&mut buffer)?;
+        object_writer.member("a", "b")?;
+        object_writer.end()?;
+        assert_eq!(buffer.freeze(), "{\"a\":\"b\"}");
+
+        let mut writer = BytesWriter::new(BytesMut::new());
+        let mut array = write_array(&mut writer)?;
+        array.value(1.5f64)?;
+        array.end()?;
+        assert_eq!(writer.freeze(), "[1.5]");
+
+        // fixed size buffers fail when they are full
+        let mut slice = [0u8; 4];
+        let mut writer = BytesWriter::new(&mut slice[..]);
+        assert!(crate::write_value(&mut writer, "abc").is_err());
+
+        Ok(())
+    }
+}
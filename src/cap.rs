@@ -0,0 +1,477 @@
+use crate::{write_string, JSONArrayWriter, JSONObjectWriter, JSONWriterValue, WriteResult};
+
+///
+/// Limits the number of values written to each array.
+///
+/// Values beyond the limit are counted but not serialized.
+/// Instead a summary of the omitted values is appended as last array entry.
+///
+/// Use [`JSONObjectWriter::with_array_cap`] or [`JSONArrayWriter::with_array_cap`] to apply it.
+///
+/// ```
+/// use json_writer::{write_object, ArrayCap, OmittedSummary};
+///
+/// let numbers: Vec<u32> = (0..1000).collect();
+/// let mut object_str = String::new();
+/// let mut object_writer = write_object(&mut object_str)
+///     .unwrap()
+///     .with_array_cap(ArrayCap::new(3));
+/// object_writer.member("numbers", &numbers).unwrap();
+/// object_writer.end().unwrap();
+/// assert_eq!(&object_str, "{\"numbers\":[0,1,2,\"... 997 more\"]}");
+///
+/// let mut object_str = String::new();
+/// let mut object_writer = write_object(&mut object_str)
+///     .unwrap()
+///     .with_array_cap(ArrayCap::new(2).summary(OmittedSummary::Object("omitted")));
+/// object_writer.member("numbers", &numbers).unwrap();
+/// object_writer.end().unwrap();
+/// assert_eq!(&object_str, "{\"numbers\":[0,1,{\"omitted\":998}]}");
+/// ```
+///
+#[derive(Debug, Copy, Clone)]
+pub struct ArrayCap {
+    max_values: usize,
+    summary: OmittedSummary,
+}
+
+///
+/// Entry appended to arrays that were cut by an [`ArrayCap`].
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OmittedSummary {
+    /// A string like `"... 1234 more"`.
+    Text,
+    /// An object with the given key, like `{"omitted":1234}`.
+    Object(&'static str),
+}
+
+///
+/// Helper for appending a JSON object whose arrays are limited by an [`ArrayCap`].
+///
+/// Can be created with [`JSONObjectWriter::with_array_cap`].
+///
+/// Appends '}' when closed.
+///
+pub struct CappedObjectWriter<'a, W: core::fmt::Write> {
+    pub(crate) object: JSONObjectWriter<'a, W>,
+    pub(crate) cap: ArrayCap,
+}
+
+///
+/// Helper for appending a JSON array with at most [`ArrayCap::max_values`] entries.
+///
+/// Can be created with [`JSONArrayWriter::with_array_cap`].
+///
+/// Appends the summary of omitted values and ']' when closed.
+///
+pub struct CappedArrayWriter<'a, W: core::fmt::Write> {
+    pub(crate) array: JSONArrayWriter<'a, W>,
+    pub(crate) cap: ArrayCap,
+    pub(crate) len: usize,
+    pub(crate) omitted: usize,
+}
+
+impl ArrayCap {
+    ///
+    /// Creates a new cap that writes at most `max_values` values per array.
+    ///
+    /// Summarizes the omitted values with [`OmittedSummary::Text`].
+    ///
+    pub fn new(max_values: usize) -> ArrayCap {
+        ArrayCap {
+            max_values,
+            summary: OmittedSummary::Text,
+        }
+    }
+
+    ///
+    /// Sets how the omitted values are summarized.
+    ///
+    pub fn summary(mut self, summary: OmittedSummary) -> ArrayCap {
+        self.summary = summary;
+        self
+    }
+
+    ///
+    /// Returns the maximum number of values per array.
+    ///
+    pub fn max_values(&self) -> usize {
+        self.max_values
+    }
+}
+
+impl<'a, W: core::fmt::Write> JSONObjectWriter<'a, W> {
+    ///
+    /// Limits the number of values of all arrays written by this writer and its nested writers.
+    ///
+    /// See [`ArrayCap`].
+    ///
+    pub fn with_array_cap(self, cap: ArrayCap) -> CappedObjectWriter<'a, W> {
+        CappedObjectWriter { object: self, cap }
+    }
+}
+
+impl<'a, W: core::fmt::Write> JSONArrayWriter<'a, W> {
+    ///
+    /// Limits the number of values of this array and all arrays written by its nested writers.
+    ///
+    /// Values that were written before are not counted. See [`ArrayCap`].
+    ///
+    pub fn with_array_cap(self, cap: ArrayCap) -> CappedArrayWriter<'a, W> {
+        CappedArrayWriter {
+            array: self,
+            cap,
+            len: 0,
+            omitted: 0,
+        }
+    }
+}
+
+impl<'a, W: core::fmt::Write> CappedObjectWriter<'a, W> {
+    ///
+    /// Starts writing a nested object with given key, see [`JSONObjectWriter::object`].
+    ///
+    #[inline(always)]
+    pub fn object<'b>(
+        &'b mut self,
+        key: &str,
+    ) -> Result<CappedObjectWriter<'b, W>, core::fmt::Error> {
+        let cap = self.cap;
+        Ok(self.object.object(key)?.with_array_cap(cap))
+    }
+
+    ///
+    /// Starts writing a nested array with given key, see [`JSONObjectWriter::array`].
+    ///
+    /// The array is limited by the [`ArrayCap`] of this writer.
+    ///
+    #[inline(always)]
+    pub fn array<'b>(
+        &'b mut self,
+        key: &str,
+    ) -> Result<CappedArrayWriter<'b, W>, core::fmt::Error> {
+        let cap = self.cap;
+        Ok(self.object.array(key)?.with_array_cap(cap))
+    }
+
+    ///
+    /// Appends a new object member to the buffer, see [`JSONObjectWriter::member`].
+    ///
+    /// Arrays in the value are limited by the [`ArrayCap`] of this writer.
+    ///
+    #[inline(always)]
+    pub fn member<T: JSONWriterValue>(&mut self, key: &str, value: T) -> WriteResult {
+        self.object.write_key(key)?;
+        value.write_json_capped(self.object.writer, self.cap)
+    }
+
+    ///
+    /// Writes a key without any value, see [`JSONObjectWriter::write_key`].
+    ///
+    pub fn write_key(&mut self, key: &str) -> WriteResult {
+        self.object.write_key(key)
+    }
+
+    ///
+    /// Writes a comma unless at the beginning of the object, see [`JSONObjectWriter::write_comma`].
+    ///
+    pub fn write_comma(&mut self) -> WriteResult {
+        self.object.write_comma()
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated writer.
+    ///
+    pub fn writer(&self) -> &W {
+        self.object.writer()
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated writer.
+    ///
+    /// See [`JSONObjectWriter::writer_mut`].
+    ///
+    pub fn writer_mut(&mut self) -> &mut W {
+        self.object.writer_mut()
+    }
+
+    ///
+    /// Consumes this writer.
+    ///
+    /// Writes '}' to the encapsulated writer.
+    ///
+    /// Prefer using this method instead of dropping the writer directly because
+    /// dropping ignores any errors the encapsulated writer might produce.
+    ///
+    #[inline(always)]
+    pub fn end(self) -> WriteResult {
+        self.object.end()
+    }
+}
+
+impl<'a, W: core::fmt::Write> CappedArrayWriter<'a, W> {
+    ///
+    /// Starts writing a nested object as array entry.
+    ///
+    /// Writes '{' and returns a CappedObjectWriter.
+    ///
+    /// Like [`CappedArrayWriter::value`], the object is only counted as omitted
+    /// and `None` is returned if the [`ArrayCap`] of this array is reached.
+    ///
+    #[inline(always)]
+    pub fn object(&mut self) -> Result<Option<CappedObjectWriter<'_, W>>, core::fmt::Error> {
+        if self.remaining() == 0 {
+            self.omitted += 1;
+            return Ok(None);
+        }
+        self.write_comma()?;
+        let object = JSONObjectWriter::new(self.array.writer)?;
+        Ok(Some(object.with_array_cap(self.cap)))
+    }
+
+    ///
+    /// Starts writing a nested array as array entry.
+    ///
+    /// Writes '[' and returns a CappedArrayWriter.
+    ///
+    /// Like [`CappedArrayWriter::value`], the array is only counted as omitted
+    /// and `None` is returned if the [`ArrayCap`] of this array is reached.
+    ///
+    #[inline(always)]
+    pub fn array(&mut self) -> Result<Option<CappedArrayWriter<'_, W>>, core::fmt::Error> {
+        if self.remaining() == 0 {
+            self.omitted += 1;
+            return Ok(None);
+        }
+        self.write_comma()?;
+        let array = JSONArrayWriter::new(self.array.writer)?;
+        Ok(Some(array.with_array_cap(self.cap)))
+    }
+
+    ///
+    /// Writes given value as array entry.
+    ///
+    /// Writes ",value" to the buffer.
+    /// The ',' is only written if this is the first member.
+    ///
+    /// If the [`ArrayCap`] of this array is reached, the value is only counted as omitted.
+    ///
+    #[inline(always)]
+    pub fn value<T: JSONWriterValue>(&mut self, value: T) -> WriteResult {
+        if self.remaining() == 0 {
+            self.omitted += 1;
+            return Ok(());
+        }
+        self.write_comma()?;
+        value.write_json_capped(self.array.writer, self.cap)
+    }
+
+    ///
+    /// Counts `count` values as omitted without writing them.
+    ///
+    /// A summary of the omitted values is appended when the array is closed.
+    ///
+    pub fn omit(&mut self, count: usize) {
+        self.omitted += count;
+    }
+
+    ///
+    /// Returns how many more values can be written before the [`ArrayCap`] of this array is reached.
+    ///
+    pub fn remaining(&self) -> usize {
+        self.cap.max_values.saturating_sub(self.len)
+    }
+
+    ///
+    /// Writes a comma unless at the beginning of the array and counts the following value.
+    ///
+    /// <p style="background:rgba(255,181,77,0.16);padding:0.75em;">
+    /// <strong>Warning:</strong>
+    /// If you use this method, you will have to write the value to the buffer yourself afterwards.
+    /// </p>
+    ///
+    pub fn write_comma(&mut self) -> WriteResult {
+        self.len += 1;
+        self.array.write_comma()
+    }
+
+    ///
+    /// Writes the summary of omitted values, if any.
+    ///
+    #[inline(never)]
+    fn write_omitted(&mut self) -> WriteResult {
+        if self.omitted == 0 {
+            return Ok(());
+        }
+        let omitted = core::mem::take(&mut self.omitted);
+        self.array.write_comma()?;
+        let writer = &mut *self.array.writer;
+        let mut buf = itoa::Buffer::new();
+        match self.cap.summary {
+            OmittedSummary::Text => {
+                writer.write_str("\"... ")?;
+                writer.write_str(buf.format(omitted))?;
+                writer.write_str(" more\"")
+            }
+            OmittedSummary::Object(key) => {
+                writer.write_char('{')?;
+                write_string(writer, key)?;
+                writer.write_char(':')?;
+                writer.write_str(buf.format(omitted))?;
+                writer.write_char('}')
+            }
+        }
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated writer.
+    ///
+    pub fn writer(&self) -> &W {
+        self.array.writer()
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated writer.
+    ///
+    /// See [`JSONArrayWriter::writer_mut`].
+    ///
+    pub fn writer_mut(&mut self) -> &mut W {
+        self.array.writer_mut()
+    }
+
+    ///
+    /// Consumes this writer.
+    ///
+    /// Writes the summary of omitted values and ']' to the encapsulated writer.
+    ///
+    /// Prefer using this method instead of dropping the writer directly because
+    /// dropping ignores any errors the encapsulated writer might produce.
+    ///
+    #[inline(always)]
+    pub fn end(mut self) -> WriteResult {
+        let result = self
+            .write_omitted()
+            .and_then(|()| self.array.writer.write_char(']'));
+        // make sure we don't write it twice, this also forgets the encapsulated array writer
+        core::mem::forget(self);
+        result
+    }
+}
+
+///
+/// Dropping ignores any errors that might occur in the encapsulated writer.
+///
+impl<W: core::fmt::Write> Drop for CappedArrayWriter<'_, W> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        // ']' is written when the encapsulated array writer is dropped
+        let _ignored = self.write_omitted();
+    }
+}
+
+///
+/// Writes the `len` values as a JSON array with at most [`ArrayCap::max_values`] entries.
+///
+/// Used by the [`JSONWriterValue::write_json_capped`] implementations of collections.
+/// Omitted values are only counted, they are not taken from the iterator.
+///
+pub(crate) fn write_capped_values<W, T, I>(
+    output_buffer: &mut W,
+    values: I,
+    len: usize,
+    cap: ArrayCap,
+) -> WriteResult
+where
+    W: core::fmt::Write,
+    T: JSONWriterValue,
+    I: Iterator<Item = T>,
+{
+    let mut array = JSONArrayWriter::new(output_buffer)?.with_array_cap(cap);
+    let written = usize::min(len, cap.max_values);
+    for value in values.take(written) {
+        array.value(value)?;
+    }
+    array.omit(len - written);
+    array.end()
+}
+
+///
+/// Writes the members as a JSON object whose arrays are limited by `cap`.
+///
+/// Used by the [`JSONWriterValue::write_json_capped`] implementations of maps.
+///
+pub(crate) fn write_capped_members<W, K, T, I>(
+    output_buffer: &mut W,
+    members: I,
+    cap: ArrayCap,
+) -> WriteResult
+where
+    W: core::fmt::Write,
+    K: AsRef<str>,
+    T: JSONWriterValue,
+    I: Iterator<Item = (K, T)>,
+{
+    let mut object = JSONObjectWriter::new(output_buffer)?.with_array_cap(cap);
+    for (key, value) in members {
+        object.member(key.as_ref(), value)?;
+    }
+    object.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{write_array, write_object};
+
+    #[test]
+    fn test_array_cap() -> Result<(), std::fmt::Error> {
+        let numbers: Vec<u8> = (0..10).collect();
+        let strings = vec!["a", "b"];
+        let mut map = std::collections::BTreeMap::new();
+        map.insert("numbers", numbers.clone());
+
+        let mut buffer = String::new();
+        let mut array = write_array(&mut buffer)?.with_array_cap(ArrayCap::new(4));
+        array.value(&numbers)?;
+        array.value(&strings)?;
+        let mut nested = array.object()?.unwrap();
+        nested.member("strings", &strings)?;
+        nested.end()?;
+        array.value(&map)?;
+        // nested containers count against the cap like values
+        assert!(array.object()?.is_none());
+        assert!(array.array()?.is_none());
+        array.value(1u8)?;
+        array.omit(10);
+        assert_eq!(array.remaining(), 0);
+        array.end()?;
+
+        assert_eq!(
+            buffer,
+            "[[0,1,2,3,\"... 6 more\"],[\"a\",\"b\"],{\"strings\":[\"a\",\"b\"]},{\"numbers\":[0,1,2,3,\"... 6 more\"]},\"... 13 more\"]"
+        );
+
+        let mut buffer = String::new();
+        let mut object_writer = write_object(&mut buffer)?
+            .with_array_cap(ArrayCap::new(1).summary(OmittedSummary::Object("omitted")));
+        let mut nested = object_writer.array("strings")?;
+        nested.value(&strings)?;
+        nested.value(&strings)?;
+        drop(nested);
+        object_writer.end()?;
+        assert_eq!(
+            buffer,
+            "{\"strings\":[[\"a\",{\"omitted\":1}],{\"omitted\":1}]}"
+        );
+
+        // no summary without omitted values
+        let mut buffer = String::new();
+        let mut array = write_array(&mut buffer)?.with_array_cap(ArrayCap::new(2));
+        array.value(&strings)?;
+        array.end()?;
+        assert_eq!(buffer, "[[\"a\",\"b\"]]");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,506 @@
+use crate::scan::{Container, Event, Scanner};
+use alloc::borrow::ToOwned;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeSet as HashSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+///
+/// Error detected by a [`CheckedWriter`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckError {
+    /// The same key was written twice to one object. Contains the key as it was written (escaped).
+    DuplicateKey(String),
+    /// The written bytes do not form valid JSON.
+    InvalidSyntax,
+    /// Containers are nested deeper than [`CheckedWriter::max_depth`].
+    DepthExceeded,
+    /// The output would become longer than [`CheckedWriter::max_bytes`].
+    SizeExceeded,
+    /// A string or key is longer than [`CheckedWriter::max_string_len`].
+    StringTooLong,
+    /// An object or array has more members than [`CheckedWriter::max_members`].
+    TooManyMembers,
+}
+
+impl core::fmt::Display for CheckError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CheckError::DuplicateKey(key) => write!(f, "duplicate key \"{}\"", key),
+            CheckError::InvalidSyntax => f.write_str("invalid JSON syntax"),
+            CheckError::DepthExceeded => f.write_str("maximum nesting depth exceeded"),
+            CheckError::SizeExceeded => f.write_str("maximum output size exceeded"),
+            CheckError::StringTooLong => f.write_str("maximum string length exceeded"),
+            CheckError::TooManyMembers => f.write_str("maximum number of members exceeded"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CheckError {}
+
+///
+/// Writer that checks the JSON passing through it before forwarding it to the encapsulated writer.
+///
+/// Detects duplicate keys in objects, including keys written by the `HashMap` and
+/// `BTreeMap` implementations of [`crate::JSONWriterValue`].
+///
+/// Optionally limits the nesting depth, the total output size, the length of strings and the number
+/// of members per object or array. This protects consumers from unbounded output when serializing
+/// untrusted data structures.
+///
+/// When a check fails, the offending write is not forwarded, it returns [`core::fmt::Error`]
+/// and all following writes fail as well. The output is incomplete in this case.
+/// Use [`CheckedWriter::error`] to find out what went wrong.
+///
+/// Keys are compared in their escaped form.
+///
+/// ```
+/// use json_writer::{write_object, CheckedWriter, CheckError};
+///
+/// let mut writer = CheckedWriter::new(String::new());
+/// let mut object_writer = write_object(&mut writer).unwrap();
+/// object_writer.member("number", 42i32).unwrap();
+/// assert!(object_writer.member("number", 43i32).is_err());
+/// drop(object_writer);
+///
+/// assert_eq!(writer.error(), Some(&CheckError::DuplicateKey("number".to_owned())));
+/// ```
+///
+/// Limits:
+///
+/// ```
+/// use json_writer::{write_value, CheckedWriter, CheckError};
+///
+/// let nested = vec![vec![vec![1u8]]];
+/// let mut writer = CheckedWriter::new(String::new()).max_depth(2);
+/// assert!(write_value(&mut writer, &nested).is_err());
+/// assert_eq!(writer.error(), Some(&CheckError::DepthExceeded));
+/// ```
+///
+pub struct CheckedWriter<W: core::fmt::Write> {
+    writer: W,
+    scanner: Scanner,
+    check_duplicate_keys: bool,
+    /// Keys of all open objects, innermost last. Entries beyond `open_objects` are kept for reuse.
+    keys: Vec<KeySet>,
+    open_objects: usize,
+    current_key: Vec<u8>,
+    open: Vec<OpenContainer>,
+    string_len: usize,
+    bytes_written: usize,
+    max_depth: usize,
+    max_bytes: usize,
+    max_string_len: usize,
+    max_members: usize,
+    error: Option<CheckError>,
+}
+
+impl<W: core::fmt::Write> CheckedWriter<W> {
+    ///
+    /// Creates a new CheckedWriter that forwards to `writer`.
+    ///
+    /// Duplicate keys are checked by default, there are no limits.
+    ///
+    pub fn new(writer: W) -> CheckedWriter<W> {
+        CheckedWriter {
+            writer,
+            scanner: Scanner::new(),
+            check_duplicate_keys: true,
+            keys: Vec::new(),
+            open_objects: 0,
+            current_key: Vec::new(),
+            open: Vec::new(),
+            string_len: 0,
+            bytes_written: 0,
+            max_depth: usize::MAX,
+            max_bytes: usize::MAX,
+            max_string_len: usize::MAX,
+            max_members: usize::MAX,
+            error: None,
+        }
+    }
+
+    ///
+    /// Enables or disables the duplicate key check.
+    ///
+    pub fn check_duplicate_keys(mut self, enabled: bool) -> CheckedWriter<W> {
+        self.check_duplicate_keys = enabled;
+        self
+    }
+
+    ///
+    /// Limits how deeply objects and arrays may be nested. A top-level object has depth 1.
+    ///
+    pub fn max_depth(mut self, max_depth: usize) -> CheckedWriter<W> {
+        self.max_depth = max_depth;
+        self
+    }
+
+    ///
+    /// Limits the total number of bytes forwarded to the encapsulated writer.
+    ///
+    pub fn max_bytes(mut self, max_bytes: usize) -> CheckedWriter<W> {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    ///
+    /// Limits the length of strings and keys in bytes, measured after escaping and without quotes.
+    ///
+    pub fn max_string_len(mut self, max_string_len: usize) -> CheckedWriter<W> {
+        self.max_string_len = max_string_len;
+        self
+    }
+
+    ///
+    /// Limits the number of members of each object and the number of values of each array.
+    ///
+    pub fn max_members(mut self, max_members: usize) -> CheckedWriter<W> {
+        self.max_members = max_members;
+        self
+    }
+
+    ///
+    /// Returns the number of bytes forwarded to the encapsulated writer so far.
+    ///
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    ///
+    /// Returns the error that stopped this writer, if any.
+    ///
+    pub fn error(&self) -> Option<&CheckError> {
+        self.error.as_ref()
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated writer.
+    ///
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated writer.
+    ///
+    /// Anything written directly to the encapsulated writer is not checked.
+    ///
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    ///
+    /// Consumes this writer and returns the encapsulated writer.
+    ///
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    fn check(&mut self, s: &str) -> Result<(), CheckError> {
+        if s.len() > self.max_bytes - self.bytes_written {
+            return Err(CheckError::SizeExceeded);
+        }
+        for &byte in s.as_bytes() {
+            let event = self
+                .scanner
+                .push(byte)
+                .map_err(|_| CheckError::InvalidSyntax)?;
+            if let Some(parent) = self.open.last_mut() {
+                let is_member = match parent.container {
+                    Container::Object => event == Event::StringStart { key: true },
+                    Container::Array => event.starts_value(),
+                };
+                if is_member {
+                    parent.members += 1;
+                    if parent.members > self.max_members {
+                        return Err(CheckError::TooManyMembers);
+                    }
+                }
+            }
+            match event {
+                Event::Open(container) => {
+                    if self.open.len() == self.max_depth {
+                        return Err(CheckError::DepthExceeded);
+                    }
+                    self.open.push(OpenContainer {
+                        container,
+                        members: 0,
+                    });
+                    // objects are tracked even without the check, so it can be enabled at any time
+                    if container == Container::Object {
+                        if self.keys.len() == self.open_objects {
+                            self.keys.push(KeySet::new());
+                        } else {
+                            self.keys[self.open_objects].clear();
+                        }
+                        self.open_objects += 1;
+                    }
+                }
+                Event::Close(container) => {
+                    self.open.pop();
+                    if container == Container::Object {
+                        self.open_objects -= 1;
+                    }
+                }
+                Event::StringStart { key } => {
+                    self.string_len = 0;
+                    if key {
+                        self.current_key.clear();
+                    }
+                }
+                Event::StringByte { key } => {
+                    self.string_len += 1;
+                    if self.string_len > self.max_string_len {
+                        return Err(CheckError::StringTooLong);
+                    }
+                    if key && self.check_duplicate_keys {
+                        self.current_key.push(byte);
+                    }
+                }
+                Event::StringEnd { key: true } if self.check_duplicate_keys => {
+                    // chunks are always complete str slices, so the key is valid utf-8
+                    let key = core::str::from_utf8(&self.current_key)
+                        .map_err(|_| CheckError::InvalidSyntax)?;
+                    if !self.keys[self.open_objects - 1].insert(key) {
+                        return Err(CheckError::DuplicateKey(key.to_owned()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: core::fmt::Write> core::fmt::Write for CheckedWriter<W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        if self.error.is_some() {
+            return Err(core::fmt::Error);
+        }
+        if let Err(error) = self.check(s) {
+            self.error = Some(error);
+            return Err(core::fmt::Error);
+        }
+        self.writer.write_str(s)?;
+        self.bytes_written += s.len();
+        Ok(())
+    }
+}
+
+///
+/// Member count of an open container.
+///
+struct OpenContainer {
+    container: Container,
+    members: usize,
+}
+
+/// Objects with up to this many members are checked by a linear search.
+const MAX_FEW_KEYS: usize = 8;
+
+///
+/// Keys of a single object.
+///
+/// Most objects only have a few members, a linear search is faster than hashing for those.
+///
+enum KeySet {
+    Few(Vec<String>),
+    Many(HashSet<String>),
+}
+
+impl KeySet {
+    fn new() -> KeySet {
+        KeySet::Few(Vec::new())
+    }
+
+    fn clear(&mut self) {
+        match self {
+            KeySet::Few(keys) => keys.clear(),
+            KeySet::Many(_) => *self = KeySet::new(),
+        }
+    }
+
+    ///
+    /// Returns false if the key is already present.
+    ///
+    fn insert(&mut self, key: &str) -> bool {
+        match self {
+            KeySet::Few(keys) => {
+                if keys.iter().any(|k| k == key) {
+                    return false;
+                }
+                if keys.len() < MAX_FEW_KEYS {
+                    keys.push(key.to_owned());
+                } else {
+                    let mut set: HashSet<String> = keys.drain(..).collect();
+                    set.insert(key.to_owned());
+                    *self = KeySet::Many(set);
+                }
+                true
+            }
+            KeySet::Many(keys) => keys.insert(key.to_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{write_array, write_object, write_value, Null};
+
+    #[test]
+    fn test_duplicate_keys_detected() -> Result<(), core::fmt::Error> {
+        let mut writer = CheckedWriter::new(String::new());
+        let mut object_writer = write_object(&mut writer)?;
+        object_writer.member("a", 1u8)?;
+        let mut nested = object_writer.object("nested")?;
+        // same key in a different object is fine
+        nested.member("a", 2u8)?;
+        nested.end()?;
+        assert!(object_writer.member("a", 3u8).is_err());
+        drop(object_writer);
+
+        assert_eq!(
+            writer.error(),
+            Some(&CheckError::DuplicateKey("a".to_owned()))
+        );
+        // the closing quote of the duplicate key is not written
+        assert_eq!(writer.writer(), "{\"a\":1,\"nested\":{\"a\":2},\"a");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_many_keys() -> Result<(), core::fmt::Error> {
+        let mut writer = CheckedWriter::new(String::new());
+        let mut array = write_array(&mut writer)?;
+        for _ in 0..3 {
+            let mut object_writer = array.object()?;
+            for i in 0..100u8 {
+                object_writer.member(&i.to_string(), i)?;
+            }
+            object_writer.end()?;
+        }
+        let mut object_writer = array.object()?;
+        for i in 0..100u8 {
+            object_writer.member(&i.to_string(), i)?;
+        }
+        assert!(object_writer.member("99", 0u8).is_err());
+        drop(object_writer);
+        drop(array);
+
+        assert_eq!(
+            writer.error(),
+            Some(&CheckError::DuplicateKey("99".to_owned()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_keys_from_map() -> Result<(), core::fmt::Error> {
+        // different keys that serialize to the same string
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct Key(u8, &'static str);
+        impl AsRef<str> for Key {
+            fn as_ref(&self) -> &str {
+                self.1
+            }
+        }
+
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(Key(0, "x"), 1u8);
+        map.insert(Key(1, "x"), 2u8);
+
+        let mut writer = CheckedWriter::new(String::new());
+        let mut object_writer = write_object(&mut writer)?;
+        assert!(object_writer.member("map", &map).is_err());
+        drop(object_writer);
+        assert_eq!(
+            writer.error(),
+            Some(&CheckError::DuplicateKey("x".to_owned()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_disabled_and_syntax() -> Result<(), core::fmt::Error> {
+        let mut writer = CheckedWriter::new(String::new()).check_duplicate_keys(false);
+        let mut object_writer = write_object(&mut writer)?;
+        object_writer.member("number", 42i32)?;
+        object_writer.member("number", 43i32)?;
+        object_writer.end()?;
+        assert_eq!(writer.writer(), "{\"number\":42,\"number\":43}");
+
+        // enabled while an object is open
+        let mut writer = CheckedWriter::new(String::new()).check_duplicate_keys(false);
+        core::fmt::Write::write_str(&mut writer, "{\"a\":1")?;
+        let mut writer = writer.check_duplicate_keys(true);
+        core::fmt::Write::write_str(&mut writer, ",\"b\":2")?;
+        assert!(core::fmt::Write::write_str(&mut writer, ",\"b\"").is_err());
+        assert_eq!(
+            writer.error(),
+            Some(&CheckError::DuplicateKey("b".to_owned()))
+        );
+
+        let mut writer = CheckedWriter::new(String::new());
+        let mut array = write_array(&mut writer)?;
+        array.write_comma()?;
+        assert!(core::fmt::Write::write_str(array.writer_mut(), "}").is_err());
+        drop(array);
+        assert_eq!(writer.error(), Some(&CheckError::InvalidSyntax));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_limits() -> Result<(), core::fmt::Error> {
+        let numbers: [u8; 4] = [1, 2, 3, 4];
+
+        let mut writer = CheckedWriter::new(String::new()).max_members(4);
+        let mut object_writer = write_object(&mut writer)?;
+        object_writer.member("numbers", &numbers[..])?;
+        object_writer.end()?;
+
+        let mut writer = CheckedWriter::new(String::new()).max_members(3);
+        assert!(write_value(&mut writer, &numbers[..]).is_err());
+        assert_eq!(writer.error(), Some(&CheckError::TooManyMembers));
+        assert_eq!(writer.writer(), "[1,2,3,");
+
+        let mut writer = CheckedWriter::new(String::new()).max_members(1);
+        let mut object_writer = write_object(&mut writer)?;
+        object_writer.member("a", Null)?;
+        assert!(object_writer.member("b", Null).is_err());
+        drop(object_writer);
+        assert_eq!(writer.error(), Some(&CheckError::TooManyMembers));
+
+        let mut writer = CheckedWriter::new(String::new()).max_string_len(3);
+        write_value(&mut writer, "abc")?;
+        assert!(write_value(&mut writer, "ab\n").is_err());
+        assert_eq!(writer.error(), Some(&CheckError::StringTooLong));
+
+        let mut writer = CheckedWriter::new(String::new()).max_bytes(9);
+        assert!(write_value(&mut writer, &numbers[..]).is_ok());
+        assert_eq!(writer.bytes_written(), 9);
+        assert!(write_value(&mut writer, Null).is_err());
+        assert_eq!(writer.error(), Some(&CheckError::SizeExceeded));
+        assert_eq!(writer.writer(), "[1,2,3,4]");
+
+        let mut writer = CheckedWriter::new(String::new()).max_depth(2);
+        let mut array = write_array(&mut writer)?;
+        let mut nested = array.array()?;
+        assert!(nested.array().is_err());
+        drop(nested);
+        drop(array);
+        assert_eq!(writer.error(), Some(&CheckError::DepthExceeded));
+        assert_eq!(writer.writer(), "[[");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+use crate::scan::{Event, Scanner};
+use crate::IoWriter;
+
+///
+/// Encoders whose output can be completed without consuming them.
+///
+trait Finish: std::io::Write {
+    ///
+    /// Writes the remaining compressed output. Does nothing if it was written already.
+    ///
+    fn finish_stream(&mut self) -> std::io::Result<()>;
+}
+
+#[cfg(feature = "gzip")]
+impl<W: std::io::Write> Finish for flate2::write::GzEncoder<W> {
+    #[inline(always)]
+    fn finish_stream(&mut self) -> std::io::Result<()> {
+        self.try_finish()
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl<W: std::io::Write> Finish for zstd::stream::write::Encoder<'static, W> {
+    #[inline(always)]
+    fn finish_stream(&mut self) -> std::io::Result<()> {
+        self.do_finish()
+    }
+}
+
+///
+/// Sink shared by the compressing writers.
+///
+/// Finishes the compressed stream as soon as the top-level object or array is closed,
+/// or when it is dropped.
+///
+struct Compressor<E: Finish> {
+    /// Only taken by [`Compressor::into_encoder`].
+    sink: Option<IoWriter<E>>,
+    /// Tracks the structure of the output, `None` if the output is not valid JSON.
+    scanner: Option<Scanner>,
+    finished: bool,
+    error: Option<std::io::Error>,
+}
+
+impl<E: Finish> Compressor<E> {
+    fn new(encoder: E) -> Compressor<E> {
+        Compressor {
+            sink: Some(IoWriter::new(encoder)),
+            scanner: Some(Scanner::new()),
+            finished: false,
+            error: None,
+        }
+    }
+
+    fn sink(&self) -> &IoWriter<E> {
+        // only taken when consumed
+        self.sink.as_ref().unwrap()
+    }
+
+    fn sink_mut(&mut self) -> &mut IoWriter<E> {
+        // only taken when consumed
+        self.sink.as_mut().unwrap()
+    }
+
+    fn take_error(&mut self) -> Option<std::io::Error> {
+        self.error.take().or_else(|| self.sink_mut().take_error())
+    }
+
+    fn finish_stream(&mut self) -> std::io::Result<()> {
+        self.sink_mut().writer_mut().finish_stream()?;
+        self.finished = true;
+        Ok(())
+    }
+
+    ///
+    /// Finishes the compressed stream and returns the encoder.
+    ///
+    fn into_encoder(mut self) -> std::io::Result<E> {
+        self.finish_stream()?;
+        // only taken here
+        Ok(self.sink.take().unwrap().into_writer())
+    }
+
+    ///
+    /// Returns true if `s` closes the top-level object or array.
+    ///
+    fn closes_document(&mut self, s: &str) -> bool {
+        let Some(scanner) = &mut self.scanner else {
+            return false;
+        };
+        for byte in s.bytes() {
+            match scanner.push(byte) {
+                Ok(Event::Close(_)) if scanner.stack().is_empty() => return true,
+                Ok(_) => {}
+                Err(_) => {
+                    // raw output that is not JSON, only finish explicitly
+                    self.scanner = None;
+                    return false;
+                }
+            }
+        }
+        false
+    }
+}
+
+impl<E: Finish> std::fmt::Write for Compressor<E> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        if self.finished {
+            self.error = Some(std::io::Error::other(
+                "write after the compressed stream was finished",
+            ));
+            return Err(std::fmt::Error);
+        }
+        self.sink_mut().write_str(s)?;
+        if self.closes_document(s) {
+            if let Err(error) = self.finish_stream() {
+                self.error = Some(error);
+                return Err(std::fmt::Error);
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// Dropping ignores any errors that might occur while finishing the compressed stream.
+///
+impl<E: Finish> Drop for Compressor<E> {
+    fn drop(&mut self) {
+        if let Some(sink) = &mut self.sink {
+            if !self.finished {
+                let _ignored = sink.writer_mut().finish_stream();
+            }
+        }
+    }
+}
+
+///
+/// Writer that compresses the output with gzip on the fly and writes it to a [`std::io::Write`].
+///
+/// Requires the `gzip` feature.
+///
+/// The compressed stream is finished when the top-level object or array is closed,
+/// e.g. by [`crate::JSONArrayWriter::end`]. Anything written afterwards fails.
+/// Other output, like a top-level string, is finished by [`GzipWriter::finish`] or when the writer is dropped.
+///
+/// ```
+/// use json_writer::{write_array, GzipWriter};
+///
+/// let mut writer = GzipWriter::new(Vec::<u8>::new());
+/// let mut array = write_array(&mut writer).unwrap();
+/// for i in 0..1000u32 {
+///     array.value(i).unwrap();
+/// }
+/// array.end().unwrap();
+/// assert!(writer.is_finished());
+/// let compressed = writer.finish().unwrap();
+/// assert!(compressed.len() < 3000);
+/// ```
+///
+#[cfg(feature = "gzip")]
+pub struct GzipWriter<W: std::io::Write> {
+    compressor: Compressor<flate2::write::GzEncoder<W>>,
+}
+
+#[cfg(feature = "gzip")]
+impl<W: std::io::Write> GzipWriter<W> {
+    ///
+    /// Creates a new GzipWriter with the default compression level.
+    ///
+    pub fn new(writer: W) -> GzipWriter<W> {
+        GzipWriter::with_level(writer, 6)
+    }
+
+    ///
+    /// Creates a new GzipWriter with the given compression level from 0 (none) to 9 (best).
+    ///
+    pub fn with_level(writer: W, level: u32) -> GzipWriter<W> {
+        let encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::new(level));
+        GzipWriter {
+            compressor: Compressor::new(encoder),
+        }
+    }
+
+    ///
+    /// Returns the number of bytes written before compression.
+    ///
+    pub fn bytes_written(&self) -> usize {
+        self.compressor.sink().bytes_written()
+    }
+
+    ///
+    /// Returns true if the compressed stream is complete.
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.compressor.finished
+    }
+
+    ///
+    /// Returns and clears the error of the last failed write.
+    ///
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.compressor.take_error()
+    }
+
+    ///
+    /// Writes the remaining compressed output, if any, and returns the encapsulated writer.
+    ///
+    pub fn finish(self) -> std::io::Result<W> {
+        self.compressor.into_encoder()?.finish()
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<W: std::io::Write> std::fmt::Write for GzipWriter<W> {
+    #[inline(always)]
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.compressor.write_str(s)
+    }
+}
+
+///
+/// Writer that compresses the output with zstd on the fly and writes it to a [`std::io::Write`].
+///
+/// Requires the `zstd` feature.
+///
+/// The compressed stream is finished when the top-level object or array is closed,
+/// e.g. by [`crate::JSONObjectWriter::end`]. Anything written afterwards fails.
+/// Other output, like a top-level string, is finished by [`ZstdWriter::finish`] or when the writer is dropped.
+///
+/// ```
+/// use json_writer::{write_object, ZstdWriter};
+///
+/// let mut writer = ZstdWriter::new(Vec::<u8>::new()).unwrap();
+/// let mut object_writer = write_object(&mut writer).unwrap();
+/// object_writer.member("id", 1u8).unwrap();
+/// object_writer.end().unwrap();
+/// assert!(writer.is_finished());
+/// let compressed = writer.finish().unwrap();
+/// ```
+///
+#[cfg(feature = "zstd")]
+pub struct ZstdWriter<W: std::io::Write> {
+    compressor: Compressor<zstd::stream::write::Encoder<'static, W>>,
+}
+
+#[cfg(feature = "zstd")]
+impl<W: std::io::Write> ZstdWriter<W> {
+    ///
+    /// Creates a new ZstdWriter with the default compression level.
+    ///
+    pub fn new(writer: W) -> std::io::Result<ZstdWriter<W>> {
+        ZstdWriter::with_level(writer, zstd::DEFAULT_COMPRESSION_LEVEL)
+    }
+
+    ///
+    /// Creates a new ZstdWriter with the given compression level from 1 to 22.
+    ///
+    pub fn with_level(writer: W, level: i32) -> std::io::Result<ZstdWriter<W>> {
+        let encoder = zstd::stream::write::Encoder::new(writer, level)?;
+        Ok(ZstdWriter {
+            compressor: Compressor::new(encoder),
+        })
+    }
+
+    ///
+    /// Returns the number of bytes written before compression.
+    ///
+    pub fn bytes_written(&self) -> usize {
+        self.compressor.sink().bytes_written()
+    }
+
+    ///
+    /// Returns true if the compressed stream is complete.
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.compressor.finished
+    }
+
+    ///
+    /// Returns and clears the error of the last failed write.
+    ///
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.compressor.take_error()
+    }
+
+    ///
+    /// Writes the remaining compressed output, if any, and returns the encapsulated writer.
+    ///
+    pub fn finish(self) -> std::io::Result<W> {
+        self.compressor.into_encoder()?.finish()
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl<W: std::io::Write> std::fmt::Write for ZstdWriter<W> {
+    #[inline(always)]
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.compressor.write_str(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip() -> std::io::Result<()> {
+        use std::io::Read;
+
+        let numbers: Vec<u32> = (0..10000).collect();
+        let mut writer = GzipWriter::new(Vec::<u8>::new());
+        crate::write_value(&mut writer, &numbers).unwrap();
+        assert_eq!(writer.bytes_written(), crate::json_len(&numbers));
+        assert!(writer.is_finished());
+        assert!(crate::write_value(&mut writer, 1u8).is_err());
+        assert!(writer.take_error().is_some());
+        let compressed = writer.finish()?;
+
+        let mut json = String::new();
+        flate2::read::GzDecoder::new(&compressed[..]).read_to_string(&mut json)?;
+        assert_eq!(json, crate::to_json_string(&numbers));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd() -> std::io::Result<()> {
+        let numbers: Vec<u32> = (0..10000).collect();
+        let mut compressed = Vec::new();
+        let mut writer = ZstdWriter::new(&mut compressed)?;
+        crate::write_value(&mut writer, &numbers).unwrap();
+        assert!(writer.is_finished());
+        drop(writer);
+        let json = zstd::stream::decode_all(&compressed[..])?;
+        assert_eq!(json, crate::to_json_string(&numbers).as_bytes());
+
+        // top-level strings are finished when dropped
+        let mut compressed = Vec::new();
+        let mut writer = ZstdWriter::new(&mut compressed)?;
+        crate::write_value(&mut writer, "a]").unwrap();
+        assert!(!writer.is_finished());
+        drop(writer);
+        let json = zstd::stream::decode_all(&compressed[..])?;
+        assert_eq!(json, b"\"a]\"");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{JSONWriterValue, RewindableWriter};
+
+///
+/// Writer that discards everything and only counts the number of bytes written.
+///
+/// Serializing to a CountingSink runs exactly the same code as serializing to a buffer,
+/// so the count always matches the real output.
+///
+/// ```
+/// use json_writer::{write_object, CountingSink};
+///
+/// let mut sink = CountingSink::new();
+/// let mut object_writer = write_object(&mut sink).unwrap();
+/// object_writer.member("text", "a\"b").unwrap();
+/// object_writer.end().unwrap();
+///
+/// assert_eq!(sink.count(), "{\"text\":\"a\\\"b\"}".len());
+/// ```
+///
+#[derive(Debug, Default, Copy, Clone)]
+pub struct CountingSink {
+    count: usize,
+}
+
+impl CountingSink {
+    ///
+    /// Creates a new CountingSink with a count of zero.
+    ///
+    pub fn new() -> CountingSink {
+        CountingSink { count: 0 }
+    }
+
+    ///
+    /// Returns the number of bytes written so far.
+    ///
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl core::fmt::Write for CountingSink {
+    #[inline(always)]
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.count += s.len();
+        Ok(())
+    }
+}
+
+impl RewindableWriter for CountingSink {
+    #[inline(always)]
+    fn position(&self) -> usize {
+        self.count
+    }
+
+    #[inline(always)]
+    fn rewind(&mut self, position: usize) {
+        self.count = position;
+    }
+}
+
+///
+/// Returns the number of bytes [`crate::to_json_string`] would return for `value`, without allocating.
+///
+/// ```
+/// use json_writer::json_len;
+///
+/// assert_eq!(json_len("Hello World\n"), 15);
+/// assert_eq!(json_len(&[1u8, 20u8, 255u8][..]), 10);
+/// ```
+///
+pub fn json_len<T: JSONWriterValue>(value: T) -> usize {
+    let mut sink = CountingSink::new();
+    // CountingSink never returns an error in it's Write implementation.
+    value.write_json(&mut sink).unwrap();
+    sink.count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{to_json_string, Null};
+
+    #[test]
+    fn test_json_len() {
+        let strings = ["", "äöü", "\u{1}\u{1f}\"\\\n\t", "\u{2028}\u{1F600}"];
+        for string in strings {
+            assert_eq!(json_len(string), to_json_string(string).len());
+        }
+        let floats = [0.0f64, -1.5, 1e300, f64::NAN, f64::INFINITY, 1.0 / 3.0];
+        for float in floats {
+            assert_eq!(json_len(float), to_json_string(float).len());
+        }
+        assert_eq!(json_len(i32::MIN), to_json_string(i32::MIN).len());
+        assert_eq!(json_len(Null), 4);
+        assert_eq!(json_len(Option::<u8>::None), 4);
+
+        let mut map = std::collections::BTreeMap::new();
+        map.insert("a\nb", vec![1.5f32, f32::NAN]);
+        assert_eq!(json_len(&map), to_json_string(&map).len());
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{
+    write_array, write_object, InsertableWriter, JSONArrayWriter, JSONObjectWriter,
+    JSONWriterValue, WriteResult,
+};
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+///
+/// Object member that is written to a separate buffer while the parent object keeps streaming.
+///
+/// Created with [`JSONObjectWriter::deferred`] or [`JSONObjectWriter::deferred_in_place`] and
+/// merged into the parent object by [`JSONObjectWriter::end_with_deferred`] or
+/// [`JSONObjectWriter::end_with_deferred_in_place`].
+///
+/// The member value is `null` if nothing is written to the section.
+///
+/// Sections that are not passed to one of these methods are discarded when the parent object is closed
+/// with [`JSONObjectWriter::end`] or dropped.
+///
+#[derive(Debug)]
+#[must_use = "the section is discarded unless it is passed to end_with_deferred"]
+pub struct DeferredSection {
+    key: String,
+    buffer: String,
+    /// Position in the parent writer and whether the parent had members before, if reserved in place.
+    reserved: Option<(usize, bool)>,
+}
+
+impl DeferredSection {
+    fn new(key: &str, reserved: Option<(usize, bool)>) -> DeferredSection {
+        DeferredSection {
+            key: key.to_owned(),
+            buffer: String::new(),
+            reserved,
+        }
+    }
+
+    ///
+    /// Returns the key of the member.
+    ///
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    ///
+    /// Starts writing the member value as an object.
+    ///
+    /// Discards anything written to this section before.
+    ///
+    pub fn object(&mut self) -> Result<JSONObjectWriter<'_, String>, core::fmt::Error> {
+        self.buffer.clear();
+        write_object(&mut self.buffer)
+    }
+
+    ///
+    /// Starts writing the member value as an array.
+    ///
+    /// Discards anything written to this section before.
+    ///
+    pub fn array(&mut self) -> Result<JSONArrayWriter<'_, String>, core::fmt::Error> {
+        self.buffer.clear();
+        write_array(&mut self.buffer)
+    }
+
+    ///
+    /// Writes the member value.
+    ///
+    /// Discards anything written to this section before.
+    ///
+    pub fn value<T: JSONWriterValue>(&mut self, value: T) -> WriteResult {
+        self.buffer.clear();
+        self.buffer.reserve(value.size_hint().0);
+        value.write_json(&mut self.buffer)
+    }
+
+    fn json(&self) -> &str {
+        if self.buffer.is_empty() {
+            "null"
+        } else {
+            &self.buffer
+        }
+    }
+}
+
+impl<W: core::fmt::Write> JSONObjectWriter<'_, W> {
+    ///
+    /// Creates a section for a member that is written out of order.
+    ///
+    /// The section is appended as one of the last members by [`JSONObjectWriter::end_with_deferred`].
+    ///
+    /// ```
+    /// use json_writer::write_object;
+    ///
+    /// let mut object_str = String::new();
+    /// let mut object_writer = write_object(&mut object_str).unwrap();
+    /// let mut summary = object_writer.deferred("summary");
+    /// let mut total = 0u32;
+    /// let mut records = object_writer.array("records").unwrap();
+    /// for i in 1..=3u32 {
+    ///     records.value(i).unwrap();
+    ///     total += i;
+    /// }
+    /// records.end().unwrap();
+    /// let mut summary_writer = summary.object().unwrap();
+    /// summary_writer.member("total", total).unwrap();
+    /// summary_writer.end().unwrap();
+    /// object_writer.end_with_deferred([summary]).unwrap();
+    ///
+    /// assert_eq!(&object_str, "{\"records\":[1,2,3],\"summary\":{\"total\":6}}");
+    /// ```
+    ///
+    pub fn deferred(&self, key: &str) -> DeferredSection {
+        DeferredSection::new(key, None)
+    }
+
+    ///
+    /// Appends the given sections as members and closes this object.
+    ///
+    /// Sections created with [`JSONObjectWriter::deferred_in_place`] are appended as well.
+    ///
+    pub fn end_with_deferred<I: IntoIterator<Item = DeferredSection>>(
+        mut self,
+        sections: I,
+    ) -> WriteResult {
+        for section in sections {
+            self.write_key(&section.key)?;
+            self.writer.write_str(section.json())?;
+        }
+        self.end()
+    }
+}
+
+impl<W: InsertableWriter> JSONObjectWriter<'_, W> {
+    ///
+    /// Creates a section for a member that is written out of order.
+    ///
+    /// [`JSONObjectWriter::end_with_deferred_in_place`] inserts the section at the current position.
+    ///
+    /// ```
+    /// use json_writer::write_object;
+    ///
+    /// let mut object_str = String::new();
+    /// let mut object_writer = write_object(&mut object_str).unwrap();
+    /// let mut count = object_writer.deferred_in_place("count");
+    /// object_writer.member("items", &[1u8, 2u8][..]).unwrap();
+    /// count.value(2u8).unwrap();
+    /// object_writer.end_with_deferred_in_place([count]).unwrap();
+    ///
+    /// assert_eq!(&object_str, "{\"count\":2,\"items\":[1,2]}");
+    /// ```
+    ///
+    pub fn deferred_in_place(&self, key: &str) -> DeferredSection {
+        DeferredSection::new(key, Some((self.writer.position(), !self.empty)))
+    }
+
+    ///
+    /// Inserts the given sections at the positions they were created at and closes this object.
+    ///
+    /// Sections created at the same position are inserted in the order they are given.
+    /// Sections created with [`JSONObjectWriter::deferred`] are appended as last members.
+    ///
+    pub fn end_with_deferred_in_place<I: IntoIterator<Item = DeferredSection>>(
+        mut self,
+        sections: I,
+    ) -> WriteResult {
+        let mut in_place = Vec::new();
+        for section in sections {
+            match section.reserved {
+                Some(_) => in_place.push(section),
+                None => {
+                    self.write_key(&section.key)?;
+                    self.writer.write_str(section.json())?;
+                }
+            }
+        }
+        // stable, sections reserved at the same position stay in the given order
+        in_place.sort_by_key(|section| section.reserved.map(|(position, _)| position));
+
+        // Insert from the back, so the positions of the remaining sections stay valid.
+        // Sections are separated by a leading comma, unless they are the first members.
+        // In that case a trailing comma separates them from the following members.
+        let has_members = !self.empty;
+        let mut inserted = String::new();
+        let mut index = in_place.len();
+        while index > 0 {
+            let (position, members_before) = in_place[index - 1].reserved.unwrap_or_default();
+            let mut start = index - 1;
+            while start > 0 && in_place[start - 1].reserved.map(|(p, _)| p) == Some(position) {
+                start -= 1;
+            }
+
+            inserted.clear();
+            for (i, section) in in_place[start..index].iter().enumerate() {
+                if i > 0 || members_before {
+                    inserted.push(',');
+                }
+                crate::write_string(&mut inserted, &section.key)?;
+                inserted.push(':');
+                inserted.push_str(section.json());
+            }
+            if !members_before && has_members {
+                inserted.push(',');
+            }
+            self.writer.insert(position, &inserted)?;
+            index = start;
+        }
+        self.empty &= in_place.is_empty();
+        self.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::write_object;
+
+    #[test]
+    fn test_deferred_in_place() -> Result<(), core::fmt::Error> {
+        let mut buffer = String::new();
+        let mut object_writer = write_object(&mut buffer)?;
+        let first = object_writer.deferred_in_place("first");
+        let second = object_writer.deferred_in_place("second");
+        object_writer.member("a", 1u8)?;
+        let mut middle = object_writer.deferred_in_place("middle");
+        object_writer.member("b", 2u8)?;
+        let last = object_writer.deferred_in_place("last");
+        let mut appended = object_writer.deferred("appended");
+
+        let mut middle_writer = middle.array()?;
+        middle_writer.value("x")?;
+        middle_writer.end()?;
+        appended.value(3u8)?;
+        object_writer.end_with_deferred_in_place([appended, last, second, middle, first])?;
+
+        assert_eq!(
+            buffer,
+            "{\"second\":null,\"first\":null,\"a\":1,\"middle\":[\"x\"],\"b\":2,\"last\":null,\"appended\":3}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deferred_only() -> Result<(), core::fmt::Error> {
+        let mut buffer = String::new();
+        let object_writer = write_object(&mut buffer)?;
+        let first = object_writer.deferred_in_place("first");
+        let second = object_writer.deferred_in_place("second");
+        object_writer.end_with_deferred_in_place([first, second])?;
+        assert_eq!(buffer, "{\"first\":null,\"second\":null}");
+
+        let mut buffer = String::new();
+        let object_writer = write_object(&mut buffer)?;
+        object_writer.end_with_deferred_in_place([])?;
+        assert_eq!(buffer, "{}");
+
+        let mut buffer = String::new();
+        let mut object_writer = write_object(&mut buffer)?;
+        object_writer.member("a", 1u8)?;
+        let first = object_writer.deferred_in_place("first");
+        object_writer.end_with_deferred([first])?;
+        assert_eq!(buffer, "{\"a\":1,\"first\":null}");
+
+        Ok(())
+    }
+}
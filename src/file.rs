@@ -0,0 +1,307 @@
+use crate::{write_object, IoWriter, JSONArrayWriter, JSONObjectWriter, WriteResult};
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Makes the names of temporary files unique within the process.
+static TEMP_FILE_COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+///
+/// Writes an object to the file at `path`, replacing it only if writing succeeded.
+///
+/// `f` writes the members of the object. The output is written to a temporary file
+/// in the same directory, which is synced to disk and then renamed to `path`.
+/// If anything fails, the temporary file is removed and the file at `path` is left untouched.
+/// The permissions of an existing file at `path` are copied to the new file.
+///
+/// ```no_run
+/// use json_writer::write_json_file_atomic;
+///
+/// write_json_file_atomic("config.json", |config| {
+///     config.member("interval", 10u32)?;
+///     config.member("name", "logger")
+/// })
+/// .unwrap();
+/// ```
+///
+pub fn write_json_file_atomic<P, F>(path: P, f: F) -> std::io::Result<()>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&mut JSONObjectWriter<'_, IoWriter<BufWriter<File>>>) -> WriteResult,
+{
+    let path = path.as_ref();
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "path is not a file")
+    })?;
+
+    let (temp_path, file) = loop {
+        let counter = TEMP_FILE_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(file_name);
+        temp_name.push(format!(".{}.{}.tmp", std::process::id(), counter));
+        let temp_path = directory.join(temp_name);
+        match File::options()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+        {
+            Ok(file) => break (temp_path, file),
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    };
+
+    let result = copy_permissions(path, &file)
+        .and_then(|()| write_and_sync(file, f))
+        .and_then(|()| std::fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ignored = std::fs::remove_file(&temp_path);
+        return result;
+    }
+
+    // Persist the rename. Syncing a directory is only possible on unix.
+    #[cfg(unix)]
+    File::open(directory)?.sync_all()?;
+    Ok(())
+}
+
+///
+/// Gives `file` the permissions of the file at `path`, if there is one.
+///
+fn copy_permissions(path: &Path, file: &File) -> std::io::Result<()> {
+    match std::fs::metadata(path) {
+        Ok(metadata) => file.set_permissions(metadata.permissions()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+fn write_and_sync<F>(file: File, f: F) -> std::io::Result<()>
+where
+    F: FnOnce(&mut JSONObjectWriter<'_, IoWriter<BufWriter<File>>>) -> WriteResult,
+{
+    let mut sink = IoWriter::new(BufWriter::new(file));
+    let result = write_object(&mut sink).and_then(|mut object_writer| {
+        f(&mut object_writer)?;
+        object_writer.end()
+    });
+    if let Some(error) = sink.take_error() {
+        return Err(error);
+    }
+    result.map_err(|_| std::io::Error::other("formatting JSON file failed"))?;
+
+    let file = sink
+        .into_writer()
+        .into_inner()
+        .map_err(|e| e.into_error())?;
+    file.sync_all()
+}
+
+///
+/// Appends values to the top-level array of an existing JSON file.
+///
+/// The file must end with ']', optionally followed by whitespace.
+/// [`ArrayFileAppender::array`] removes the ']' and returns a [`JSONArrayWriter`] that continues the array,
+/// it writes the ']' again when it is closed. Whitespace after the ']', like a trailing newline,
+/// is restored by [`ArrayFileAppender::finish`].
+/// The file is invalid while values are appended, until the array writer is closed.
+///
+/// ```no_run
+/// use json_writer::ArrayFileAppender;
+///
+/// let mut appender = ArrayFileAppender::open("measurements.json").unwrap();
+/// let mut array = appender.array().unwrap();
+/// array.value(42u32).unwrap();
+/// array.end().unwrap();
+/// appender.finish().unwrap();
+/// ```
+///
+pub struct ArrayFileAppender {
+    sink: IoWriter<BufWriter<File>>,
+    /// Position of the closing ']'
+    end: u64,
+    /// Whitespace after the closing ']'
+    trailer: String,
+    empty: bool,
+    resumed: bool,
+}
+
+impl ArrayFileAppender {
+    ///
+    /// Opens the file at `path` and verifies that it ends with a top-level array.
+    ///
+    /// The file is not modified until [`ArrayFileAppender::array`] is called.
+    ///
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<ArrayFileAppender> {
+        let mut file = File::options().read(true).write(true).open(path)?;
+        let length = file.seek(SeekFrom::End(0))?;
+        let invalid = || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "file does not end with ']'",
+            )
+        };
+
+        let (end, last) = last_non_whitespace(&mut file, length)?.ok_or_else(invalid)?;
+        if last != b']' {
+            return Err(invalid());
+        }
+        let (_, previous) = last_non_whitespace(&mut file, end)?.ok_or_else(invalid)?;
+        let mut trailer = String::new();
+        file.seek(SeekFrom::Start(end + 1))?;
+        file.read_to_string(&mut trailer)?;
+
+        Ok(ArrayFileAppender {
+            sink: IoWriter::new(BufWriter::new(file)),
+            end,
+            trailer,
+            empty: previous == b'[',
+            resumed: false,
+        })
+    }
+
+    ///
+    /// Returns true if the array in the file has no values.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.empty
+    }
+
+    ///
+    /// Removes the closing ']' from the file and returns a writer that appends values to the array.
+    ///
+    /// Can only be called once.
+    ///
+    pub fn array(&mut self) -> std::io::Result<JSONArrayWriter<'_, IoWriter<BufWriter<File>>>> {
+        if self.resumed {
+            return Err(std::io::Error::other("array was already resumed"));
+        }
+        let file = self.sink.writer_mut().get_mut();
+        file.set_len(self.end)?;
+        file.seek(SeekFrom::Start(self.end))?;
+        self.resumed = true;
+        Ok(JSONArrayWriter::resume(&mut self.sink, self.empty))
+    }
+
+    ///
+    /// Restores the whitespace after the array, writes all buffered output to the file,
+    /// syncs it to disk and returns the file.
+    ///
+    pub fn finish(mut self) -> std::io::Result<File> {
+        if let Some(error) = self.sink.take_error() {
+            return Err(error);
+        }
+        if self.resumed {
+            std::fmt::Write::write_str(&mut self.sink, &self.trailer).map_err(|_| {
+                self.sink
+                    .take_error()
+                    .unwrap_or_else(|| std::io::Error::other("writing file failed"))
+            })?;
+        }
+        let file = self
+            .sink
+            .into_writer()
+            .into_inner()
+            .map_err(|e| e.into_error())?;
+        file.sync_data()?;
+        Ok(file)
+    }
+}
+
+///
+/// Returns the position and value of the last byte before `end` that is not whitespace.
+///
+fn last_non_whitespace(file: &mut File, end: u64) -> std::io::Result<Option<(u64, u8)>> {
+    let mut chunk = [0u8; 256];
+    let mut end = end;
+    while end > 0 {
+        let start = end.saturating_sub(chunk.len() as u64);
+        let chunk = &mut chunk[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        if let Some(index) = chunk
+            .iter()
+            .rposition(|b| !matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+        {
+            return Ok(Some((start + index as u64, chunk[index])));
+        }
+        end = start;
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_directory;
+
+    #[test]
+    fn test_write_atomic() -> std::io::Result<()> {
+        let directory = test_directory("atomic");
+        let path = directory.join("config.json");
+
+        write_json_file_atomic(&path, |config| config.member("version", 1u8))?;
+        assert_eq!(std::fs::read_to_string(&path)?, "{\"version\":1}");
+
+        let result = write_json_file_atomic(&path, |config| {
+            config.member("version", 2u8)?;
+            Err(std::fmt::Error)
+        });
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&path)?, "{\"version\":1}");
+        assert_eq!(std::fs::read_dir(&directory)?.count(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+            write_json_file_atomic(&path, |config| config.member("version", 3u8))?;
+            assert_eq!(
+                std::fs::metadata(&path)?.permissions().mode() & 0o777,
+                0o600
+            );
+        }
+
+        std::fs::remove_dir_all(&directory)
+    }
+
+    #[test]
+    fn test_append() -> std::io::Result<()> {
+        let directory = test_directory("append");
+        let path = directory.join("values.json");
+
+        std::fs::write(&path, "[ ]\n")?;
+        for i in 1u8..=2 {
+            let mut appender = ArrayFileAppender::open(&path)?;
+            assert_eq!(appender.is_empty(), i == 1);
+            let mut array = appender.array()?;
+            array.value(i).unwrap();
+            array.object().unwrap().member("i", i).unwrap();
+            array.end().unwrap();
+            assert!(appender.array().is_err());
+            appender.finish()?;
+        }
+        assert_eq!(
+            std::fs::read_to_string(&path)?,
+            "[ 1,{\"i\":1},2,{\"i\":2}]\n"
+        );
+
+        // nothing is changed if no values are appended
+        let padding = " ".repeat(300);
+        std::fs::write(&path, format!("[1]{}", padding))?;
+        ArrayFileAppender::open(&path)?.finish()?;
+        assert_eq!(std::fs::read_to_string(&path)?, format!("[1]{}", padding));
+
+        std::fs::write(&path, "{}")?;
+        assert!(ArrayFileAppender::open(&path).is_err());
+        std::fs::write(&path, " ] ")?;
+        assert!(ArrayFileAppender::open(&path).is_err());
+
+        std::fs::remove_dir_all(&directory)
+    }
+}
@@ -0,0 +1,233 @@
+use crate::JSONWriterValue;
+
+///
+/// Error returned when the output does not fit into a [`FixedBuffer`] or the slice passed to [`to_json_slice`].
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CapacityError {
+    required: usize,
+    capacity: usize,
+}
+
+impl CapacityError {
+    ///
+    /// Returns the number of bytes required for the complete output.
+    ///
+    /// Retrying with a buffer of this size succeeds.
+    ///
+    pub fn required(&self) -> usize {
+        self.required
+    }
+
+    ///
+    /// Returns the size of the buffer that was too small.
+    ///
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl core::fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "output of {} bytes exceeds buffer capacity of {} bytes",
+            self.required, self.capacity
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapacityError {}
+
+///
+/// Writer that stores the output in a byte array of fixed size, without allocating.
+///
+/// Writes never fail. If the output does not fit, the remaining output is only counted,
+/// and [`FixedBuffer::as_str`] returns a [`CapacityError`] with the required size.
+///
+/// ```
+/// use json_writer::{write_object, FixedBuffer};
+///
+/// let mut buffer = FixedBuffer::<16>::new();
+/// let mut object_writer = write_object(&mut buffer).unwrap();
+/// object_writer.member("id", 1u8).unwrap();
+/// object_writer.end().unwrap();
+/// assert_eq!(buffer.as_str(), Ok("{\"id\":1}"));
+///
+/// let mut buffer = FixedBuffer::<4>::new();
+/// let mut object_writer = write_object(&mut buffer).unwrap();
+/// object_writer.member("id", 1u8).unwrap();
+/// object_writer.end().unwrap();
+/// assert_eq!(buffer.as_str().unwrap_err().required(), 8);
+/// ```
+///
+pub struct FixedBuffer<const N: usize> {
+    buffer: [u8; N],
+    sink: SliceSink,
+}
+
+impl<const N: usize> FixedBuffer<N> {
+    ///
+    /// Creates a new empty FixedBuffer.
+    ///
+    pub fn new() -> FixedBuffer<N> {
+        FixedBuffer {
+            buffer: [0; N],
+            sink: SliceSink::default(),
+        }
+    }
+
+    ///
+    /// Returns the output, or an error if it did not fit.
+    ///
+    pub fn as_str(&self) -> Result<&str, CapacityError> {
+        self.sink.as_str(&self.buffer)
+    }
+
+    ///
+    /// Returns the number of bytes stored.
+    ///
+    pub fn len(&self) -> usize {
+        self.sink.len
+    }
+
+    ///
+    /// Returns true if nothing is stored.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.sink.len == 0
+    }
+
+    ///
+    /// Discards the output, so that the buffer can be reused.
+    ///
+    pub fn clear(&mut self) {
+        self.sink = SliceSink::default();
+    }
+}
+
+impl<const N: usize> Default for FixedBuffer<N> {
+    fn default() -> FixedBuffer<N> {
+        FixedBuffer::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Write for FixedBuffer<N> {
+    #[inline(always)]
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.sink.write(&mut self.buffer, s);
+        Ok(())
+    }
+}
+
+///
+/// Serializes the given `value` to JSON into `buffer`, without allocating.
+///
+/// Returns the part of `buffer` that contains the output,
+/// or an error with the required size if `buffer` is too small.
+///
+/// ```
+/// use json_writer::to_json_slice;
+///
+/// let mut buffer = [0u8; 8];
+/// assert_eq!(to_json_slice(&[1u8, 2u8][..], &mut buffer), Ok("[1,2]"));
+///
+/// let error = to_json_slice("too long", &mut buffer).unwrap_err();
+/// let mut buffer = vec![0u8; error.required()];
+/// assert_eq!(to_json_slice("too long", &mut buffer), Ok("\"too long\""));
+/// ```
+///
+pub fn to_json_slice<T: JSONWriterValue>(
+    value: T,
+    buffer: &mut [u8],
+) -> Result<&str, CapacityError> {
+    struct Writer<'a> {
+        buffer: &'a mut [u8],
+        sink: SliceSink,
+    }
+
+    impl core::fmt::Write for Writer<'_> {
+        #[inline(always)]
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            self.sink.write(self.buffer, s);
+            Ok(())
+        }
+    }
+
+    let mut writer = Writer {
+        buffer,
+        sink: SliceSink::default(),
+    };
+    // Writer never returns an error in it's Write implementation.
+    value.write_json(&mut writer).unwrap();
+    let Writer { buffer, sink } = writer;
+    sink.as_str(buffer)
+}
+
+///
+/// Position in a byte slice, and the number of bytes that would have been written without overflow.
+///
+#[derive(Default)]
+struct SliceSink {
+    len: usize,
+    required: usize,
+}
+
+impl SliceSink {
+    ///
+    /// Copies `s` to `buffer` if it fits completely. Once a string did not fit, nothing is copied anymore.
+    ///
+    #[inline(always)]
+    fn write(&mut self, buffer: &mut [u8], s: &str) {
+        let end = self.required + s.len();
+        if self.len == self.required && end <= buffer.len() {
+            buffer[self.len..end].copy_from_slice(s.as_bytes());
+            self.len = end;
+        }
+        self.required = end;
+    }
+
+    fn as_str<'a>(&self, buffer: &'a [u8]) -> Result<&'a str, CapacityError> {
+        if self.len != self.required {
+            return Err(CapacityError {
+                required: self.required,
+                capacity: buffer.len(),
+            });
+        }
+        // Only complete strings were copied, so the output is valid utf-8.
+        Ok(unsafe { core::str::from_utf8_unchecked(&buffer[..self.len]) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    #[test]
+    fn test_fixed_buffer() {
+        let mut buffer = FixedBuffer::<5>::new();
+        assert!(buffer.is_empty());
+        buffer.write_str("ä").unwrap();
+        buffer.write_str("ö").unwrap();
+        // does not fit and is not split
+        buffer.write_str("ü").unwrap();
+        // fits, but is not written after an overflow
+        buffer.write_str("a").unwrap();
+        assert_eq!(buffer.len(), 4);
+        let error = buffer.as_str().unwrap_err();
+        assert_eq!((error.required(), error.capacity()), (7, 5));
+        assert_eq!(
+            error.to_string(),
+            "output of 7 bytes exceeds buffer capacity of 5 bytes"
+        );
+
+        buffer.clear();
+        buffer.write_str("abcde").unwrap();
+        assert_eq!(buffer.as_str(), Ok("abcde"));
+
+        let mut empty: [u8; 0] = [];
+        assert_eq!(to_json_slice(true, &mut empty).unwrap_err().required(), 4);
+    }
+}
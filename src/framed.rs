@@ -0,0 +1,357 @@
+use crate::{
+    write_array, write_object, JSONArrayWriter, JSONObjectWriter, JSONWriterValue, WriteResult,
+};
+use std::io::SeekFrom;
+
+///
+/// Encoding of the length written before every message of a [`FramedWriter`].
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LengthPrefix {
+    /// Length as 4 byte unsigned integer, most significant byte first.
+    U32BigEndian,
+    /// Length as decimal ASCII digits, padded with leading zeros to `width` digits.
+    Decimal {
+        /// Number of digits
+        width: usize,
+    },
+}
+
+impl LengthPrefix {
+    fn len(&self) -> usize {
+        match self {
+            LengthPrefix::U32BigEndian => 4,
+            LengthPrefix::Decimal { width } => *width,
+        }
+    }
+
+    fn encode(&self, length: usize, output: &mut Vec<u8>) -> std::io::Result<()> {
+        let too_long = || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "message too long for length prefix",
+            )
+        };
+        match self {
+            LengthPrefix::U32BigEndian => {
+                let length = u32::try_from(length).map_err(|_| too_long())?;
+                output.extend_from_slice(&length.to_be_bytes());
+            }
+            LengthPrefix::Decimal { width } => {
+                let mut digits = itoa::Buffer::new();
+                let digits = digits.format(length);
+                if digits.len() > *width {
+                    return Err(too_long());
+                }
+                output.resize(output.len() + width - digits.len(), b'0');
+                output.extend_from_slice(digits.as_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// Writer for messages that are each preceded by their length in bytes.
+///
+/// Created with [`FramedWriter::new`], messages are written to an internal buffer first,
+/// which is reused for all messages.
+/// Created with [`FramedWriter::in_place`], messages are streamed to the seekable writer directly
+/// and the prefix is patched when the message is complete.
+///
+/// ```
+/// use json_writer::{FramedWriter, LengthPrefix};
+///
+/// let mut writer = FramedWriter::new(Vec::<u8>::new(), LengthPrefix::Decimal { width: 4 });
+/// writer.object(|message| message.member("id", 1u8)).unwrap();
+/// assert_eq!(writer.into_writer(), b"0008{\"id\":1}");
+///
+/// let cursor = std::io::Cursor::new(Vec::<u8>::new());
+/// let mut writer = FramedWriter::in_place(cursor, LengthPrefix::U32BigEndian);
+/// writer.value("abc").unwrap();
+/// assert_eq!(writer.into_writer().into_inner(), b"\0\0\0\x05\"abc\"");
+/// ```
+///
+pub struct FramedWriter<W: std::io::Write> {
+    writer: W,
+    prefix: LengthPrefix,
+    buffer: Vec<u8>,
+    /// Only set for seekable writers.
+    seek: Option<fn(&mut W, SeekFrom) -> std::io::Result<u64>>,
+    /// Set if a failed message could not be removed from a seekable writer.
+    broken: bool,
+    messages: u64,
+}
+
+///
+/// Sink of a single message of a [`FramedWriter`].
+///
+pub struct FrameWriter<'a, W: std::io::Write> {
+    target: FrameTarget<'a, W>,
+    len: usize,
+    error: Option<std::io::Error>,
+}
+
+enum FrameTarget<'a, W> {
+    Direct(&'a mut W),
+    Buffer(&'a mut Vec<u8>),
+}
+
+impl<W: std::io::Write> std::fmt::Write for FrameWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        match &mut self.target {
+            FrameTarget::Direct(writer) => {
+                if let Err(error) = writer.write_all(s.as_bytes()) {
+                    self.error = Some(error);
+                    return Err(std::fmt::Error);
+                }
+            }
+            FrameTarget::Buffer(buffer) => buffer.extend_from_slice(s.as_bytes()),
+        }
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write> FramedWriter<W> {
+    ///
+    /// Creates a new FramedWriter that buffers every message before writing it to `writer`.
+    ///
+    pub fn new(writer: W, prefix: LengthPrefix) -> FramedWriter<W> {
+        FramedWriter {
+            writer,
+            prefix,
+            buffer: Vec::new(),
+            seek: None,
+            broken: false,
+            messages: 0,
+        }
+    }
+
+    ///
+    /// Returns the number of messages written successfully.
+    ///
+    pub fn messages(&self) -> u64 {
+        self.messages
+    }
+
+    ///
+    /// Writes a message that is an object.
+    ///
+    /// `f` writes the members, the object is closed afterwards.
+    ///
+    pub fn object<F>(&mut self, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut JSONObjectWriter<'_, FrameWriter<'_, W>>) -> WriteResult,
+    {
+        self.message(|frame| {
+            let mut object_writer = write_object(frame)?;
+            f(&mut object_writer)?;
+            object_writer.end()
+        })
+    }
+
+    ///
+    /// Writes a message that is an array.
+    ///
+    /// `f` writes the values, the array is closed afterwards.
+    ///
+    pub fn array<F>(&mut self, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut JSONArrayWriter<'_, FrameWriter<'_, W>>) -> WriteResult,
+    {
+        self.message(|frame| {
+            let mut array_writer = write_array(frame)?;
+            f(&mut array_writer)?;
+            array_writer.end()
+        })
+    }
+
+    ///
+    /// Writes a message that is a single value.
+    ///
+    pub fn value<T: JSONWriterValue>(&mut self, value: T) -> std::io::Result<()> {
+        if self.seek.is_none() {
+            self.buffer.reserve(self.prefix.len() + value.size_hint().0);
+        }
+        self.message(|frame| value.write_json(frame))
+    }
+
+    ///
+    /// Flushes the encapsulated writer.
+    ///
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated writer.
+    ///
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated writer.
+    ///
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    ///
+    /// Consumes this writer and returns the encapsulated writer.
+    ///
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    fn message<F>(&mut self, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut FrameWriter<'_, W>) -> WriteResult,
+    {
+        match self.seek {
+            Some(seek) => self.message_in_place(seek, f)?,
+            None => self.message_buffered(f)?,
+        }
+        self.messages += 1;
+        Ok(())
+    }
+
+    ///
+    /// Writes nothing if the message fails.
+    ///
+    fn message_buffered<F>(&mut self, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut FrameWriter<'_, W>) -> WriteResult,
+    {
+        self.buffer.clear();
+        let prefix_len = self.prefix.len();
+        self.buffer.resize(prefix_len, 0);
+        let mut frame = FrameWriter {
+            target: FrameTarget::Buffer(&mut self.buffer),
+            len: 0,
+            error: None,
+        };
+        f(&mut frame).map_err(|_| format_error())?;
+        let len = frame.len;
+
+        let mut prefix = Vec::with_capacity(prefix_len);
+        self.prefix.encode(len, &mut prefix)?;
+        self.buffer[..prefix_len].copy_from_slice(&prefix);
+        self.writer.write_all(&self.buffer)
+    }
+
+    ///
+    /// Seeks back to the start of the message if it fails, so that the next message overwrites it.
+    /// If that is not possible, the writer is broken and all following messages fail.
+    ///
+    fn message_in_place<F>(
+        &mut self,
+        seek: fn(&mut W, SeekFrom) -> std::io::Result<u64>,
+        f: F,
+    ) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut FrameWriter<'_, W>) -> WriteResult,
+    {
+        if self.broken {
+            return Err(std::io::Error::other(
+                "framed writer is broken by a failed message",
+            ));
+        }
+        let start = seek(&mut self.writer, SeekFrom::Current(0))?;
+        let result = self.write_in_place(seek, start, f);
+        if result.is_err() && seek(&mut self.writer, SeekFrom::Start(start)).is_err() {
+            self.broken = true;
+        }
+        result
+    }
+
+    fn write_in_place<F>(
+        &mut self,
+        seek: fn(&mut W, SeekFrom) -> std::io::Result<u64>,
+        start: u64,
+        f: F,
+    ) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut FrameWriter<'_, W>) -> WriteResult,
+    {
+        let mut prefix = vec![0; self.prefix.len()];
+        self.writer.write_all(&prefix)?;
+
+        let mut frame = FrameWriter {
+            target: FrameTarget::Direct(&mut self.writer),
+            len: 0,
+            error: None,
+        };
+        let result = f(&mut frame);
+        if let Some(error) = frame.error {
+            return Err(error);
+        }
+        result.map_err(|_| format_error())?;
+        let len = frame.len;
+
+        prefix.clear();
+        self.prefix.encode(len, &mut prefix)?;
+        seek(&mut self.writer, SeekFrom::Start(start))?;
+        self.writer.write_all(&prefix)?;
+        seek(&mut self.writer, SeekFrom::Current(len as i64))?;
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write + std::io::Seek> FramedWriter<W> {
+    ///
+    /// Creates a new FramedWriter that streams every message to `writer` and patches the length afterwards.
+    ///
+    /// If a message fails or its length does not fit into the prefix, the writer seeks back
+    /// to the start of the message, so that the next message overwrites it.
+    /// The encapsulated writer is not truncated, so a file should be truncated to its current position
+    /// (e.g. with [`std::fs::File::set_len`]) when writing is finished.
+    ///
+    pub fn in_place(writer: W, prefix: LengthPrefix) -> FramedWriter<W> {
+        FramedWriter {
+            seek: Some(W::seek),
+            ..FramedWriter::new(writer, prefix)
+        }
+    }
+}
+
+fn format_error() -> std::io::Error {
+    std::io::Error::other("formatting framed message failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framing() -> std::io::Result<()> {
+        let prefix = LengthPrefix::Decimal { width: 2 };
+        let mut buffered = FramedWriter::new(Vec::<u8>::new(), prefix);
+        let mut in_place = FramedWriter::in_place(std::io::Cursor::new(Vec::new()), prefix);
+
+        for i in [1u32, 1000] {
+            buffered.array(|array| array.value(i))?;
+            in_place.array(|array| array.value(i))?;
+        }
+        // 100 bytes including the quotes
+        let too_long = "x".repeat(98);
+        assert!(buffered.value(&too_long).is_err());
+        assert!(in_place.value(&too_long).is_err());
+        assert!(buffered.object(|_| Err(std::fmt::Error)).is_err());
+        assert!(in_place.object(|_| Err(std::fmt::Error)).is_err());
+
+        assert_eq!(buffered.messages(), 2);
+        assert_eq!(buffered.into_writer(), b"03[1]06[1000]");
+        assert_eq!(in_place.messages(), 2);
+        assert_eq!(in_place.writer().position(), 13);
+
+        // failed messages are overwritten by the next message
+        in_place.value(7u8)?;
+        let cursor = in_place.into_writer();
+        assert_eq!(cursor.position(), 16);
+        assert_eq!(&cursor.get_ref()[..16], b"03[1]06[1000]017");
+
+        Ok(())
+    }
+}
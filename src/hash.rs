@@ -0,0 +1,208 @@
+use crate::CountingSink;
+
+///
+/// Incremental hash function used by [`HashingWriter`].
+///
+/// The output is split into chunks wherever the writers happen to call `write_str`,
+/// so the hash must only depend on the concatenated bytes, not on how they were split.
+/// This is true for streaming digests like SHA-256 or [`Fnv1a`], but not guaranteed
+/// for [`core::hash::Hasher`]s, which are therefore not supported.
+///
+/// [`Fnv1a`] is sufficient for deduplication, implement this trait for a cryptographic digest
+/// (e.g. a wrapper around a SHA-256 implementation) to compute ETags.
+///
+/// ```
+/// use json_writer::{write_value, Digest, HashingWriter};
+///
+/// /// 32-bit FNV-1a
+/// struct Fnv(u32);
+///
+/// impl Digest for Fnv {
+///     type Output = u32;
+///
+///     fn update(&mut self, bytes: &[u8]) {
+///         for byte in bytes {
+///             self.0 = (self.0 ^ *byte as u32).wrapping_mul(0x01000193);
+///         }
+///     }
+///
+///     fn finalize(self) -> u32 {
+///         self.0
+///     }
+/// }
+///
+/// let mut writer = HashingWriter::new(Fnv(0x811c9dc5));
+/// write_value(&mut writer, "a").unwrap();
+/// // hash of "\"a\""
+/// assert_eq!(writer.finalize(), 0x61a1cfea);
+/// ```
+///
+pub trait Digest {
+    ///
+    /// Type of the finished hash.
+    ///
+    type Output;
+
+    ///
+    /// Feeds `bytes` to the hash function.
+    ///
+    fn update(&mut self, bytes: &[u8]);
+
+    ///
+    /// Finishes the computation and returns the hash.
+    ///
+    fn finalize(self) -> Self::Output;
+}
+
+///
+/// The 64-bit FNV-1a hash function.
+///
+/// Fast and stable across platforms and releases, but not cryptographically secure.
+///
+#[derive(Debug, Copy, Clone)]
+pub struct Fnv1a(u64);
+
+impl Fnv1a {
+    ///
+    /// Creates a new hash with the standard offset basis.
+    ///
+    pub fn new() -> Fnv1a {
+        Fnv1a(0xcbf29ce484222325)
+    }
+}
+
+impl Default for Fnv1a {
+    fn default() -> Fnv1a {
+        Fnv1a::new()
+    }
+}
+
+impl Digest for Fnv1a {
+    type Output = u64;
+
+    #[inline(always)]
+    fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    #[inline(always)]
+    fn finalize(self) -> u64 {
+        self.0
+    }
+}
+
+///
+/// Writer that hashes all output, and optionally forwards it to another writer.
+///
+/// Without another writer nothing is buffered, so large documents can be hashed without building them.
+/// Only bytes accepted by the encapsulated writer are hashed.
+///
+/// The hash is only as stable as the output. The members of a `HashMap`
+/// are written in random order, use a [`alloc::collections::BTreeMap`] instead.
+///
+/// ```
+/// use json_writer::{write_object, Fnv1a, HashingWriter};
+///
+/// let mut writer = HashingWriter::forwarding(Fnv1a::new(), String::new());
+/// let mut object_writer = write_object(&mut writer).unwrap();
+/// object_writer.member("id", 1u8).unwrap();
+/// object_writer.end().unwrap();
+///
+/// assert_eq!(writer.writer(), "{\"id\":1}");
+/// let hash: u64 = writer.finalize();
+/// ```
+///
+pub struct HashingWriter<D: Digest, W: core::fmt::Write = CountingSink> {
+    digest: D,
+    writer: W,
+}
+
+impl<D: Digest> HashingWriter<D, CountingSink> {
+    ///
+    /// Creates a new HashingWriter that only hashes the output and discards it.
+    ///
+    /// [`HashingWriter::writer`] returns a [`CountingSink`] with the number of bytes hashed.
+    ///
+    pub fn new(digest: D) -> HashingWriter<D, CountingSink> {
+        HashingWriter::forwarding(digest, CountingSink::new())
+    }
+}
+
+impl<D: Digest, W: core::fmt::Write> HashingWriter<D, W> {
+    ///
+    /// Creates a new HashingWriter that hashes the output and forwards it to `writer`.
+    ///
+    pub fn forwarding(digest: D, writer: W) -> HashingWriter<D, W> {
+        HashingWriter { digest, writer }
+    }
+
+    ///
+    /// Finishes the hash computation and returns the hash.
+    ///
+    pub fn finalize(self) -> D::Output {
+        self.digest.finalize()
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated writer.
+    ///
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated writer.
+    ///
+    /// Anything written directly to the encapsulated writer is not hashed.
+    ///
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    ///
+    /// Consumes this writer and returns the digest and the encapsulated writer.
+    ///
+    pub fn into_parts(self) -> (D, W) {
+        (self.digest, self.writer)
+    }
+}
+
+impl<D: Digest, W: core::fmt::Write> core::fmt::Write for HashingWriter<D, W> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.writer.write_str(s)?;
+        self.digest.update(s.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{to_json_string, write_value};
+
+    #[test]
+    fn test_hash() -> Result<(), core::fmt::Error> {
+        let mut map = std::collections::BTreeMap::new();
+        map.insert("b", vec![1u32, 2, 3]);
+        map.insert("a", vec![]);
+
+        let mut writer = HashingWriter::new(Fnv1a::new());
+        write_value(&mut writer, &map)?;
+        let json = to_json_string(&map);
+        assert_eq!(writer.writer().count(), json.len());
+
+        // the hash does not depend on how the output is split
+        let mut expected = Fnv1a::new();
+        expected.update(json.as_bytes());
+        assert_eq!(writer.finalize(), expected.finalize());
+
+        let mut digest = Fnv1a::default();
+        digest.update(b"a");
+        assert_eq!(digest.finalize(), 0xaf63dc4c8601ec8c);
+
+        Ok(())
+    }
+}
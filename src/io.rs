@@ -0,0 +1,190 @@
+use crate::PatchableWriter;
+use std::io::SeekFrom;
+
+///
+/// Adapter for writing JSON to a [`std::io::Write`], e.g. a file or a socket.
+///
+/// All output is forwarded immediately, wrap the encapsulated writer in a
+/// [`std::io::BufWriter`] to reduce the number of system calls.
+///
+/// [`std::fmt::Write`] can not return the [`std::io::Error`] that occurred.
+/// It is stored instead and can be retrieved with [`IoWriter::take_error`].
+///
+/// If the encapsulated writer is seekable, members can be reserved and filled later,
+/// see [`crate::JSONObjectWriter::reserve_member`].
+///
+/// ```
+/// use json_writer::{write_array, IoWriter};
+///
+/// let mut writer = IoWriter::new(Vec::<u8>::new());
+/// let mut array = write_array(&mut writer).unwrap();
+/// for i in 1u8..=3 {
+///     array.value(i).unwrap();
+/// }
+/// array.end().unwrap();
+///
+/// assert_eq!(writer.bytes_written(), 7);
+/// assert_eq!(writer.into_writer(), b"[1,2,3]");
+/// ```
+///
+pub struct IoWriter<W: std::io::Write> {
+    writer: W,
+    bytes_written: usize,
+    error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> IoWriter<W> {
+    ///
+    /// Creates a new IoWriter that forwards everything to `writer`.
+    ///
+    pub fn new(writer: W) -> IoWriter<W> {
+        IoWriter {
+            writer,
+            bytes_written: 0,
+            error: None,
+        }
+    }
+
+    ///
+    /// Returns the number of bytes forwarded to the encapsulated writer.
+    ///
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    ///
+    /// Returns and clears the error of the last failed write.
+    ///
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.error.take()
+    }
+
+    ///
+    /// Flushes the encapsulated writer.
+    ///
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated writer.
+    ///
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated writer.
+    ///
+    /// Anything written directly to the encapsulated writer is not counted.
+    ///
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    ///
+    /// Consumes this writer and returns the encapsulated writer.
+    ///
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: std::io::Write> std::fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        match self.writer.write_all(s.as_bytes()) {
+            Ok(()) => {
+                self.bytes_written += s.len();
+                Ok(())
+            }
+            Err(error) => {
+                self.error = Some(error);
+                Err(std::fmt::Error)
+            }
+        }
+    }
+}
+
+///
+/// Overwrites the output by seeking back, e.g. in a file.
+///
+/// Positions are counted from the position of the encapsulated writer when this writer was created.
+///
+impl<W: std::io::Write + std::io::Seek> PatchableWriter for IoWriter<W> {
+    #[inline(always)]
+    fn position(&self) -> usize {
+        self.bytes_written
+    }
+
+    fn patch(&mut self, position: usize, data: &str) -> std::fmt::Result {
+        let end = position.checked_add(data.len()).ok_or(std::fmt::Error)?;
+        if end > self.bytes_written {
+            return Err(std::fmt::Error);
+        }
+        let back = (self.bytes_written - position) as i64;
+        let forward = (self.bytes_written - end) as i64;
+        let result = self
+            .writer
+            .seek(SeekFrom::Current(-back))
+            .and_then(|_| self.writer.write_all(data.as_bytes()))
+            .and_then(|()| self.writer.seek(SeekFrom::Current(forward)));
+        match result {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                self.error = Some(error);
+                Err(std::fmt::Error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::write_object;
+    use std::fmt::Write;
+    use std::io::Cursor;
+
+    struct FailingWriter;
+
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_io_error() {
+        let mut writer = IoWriter::new(FailingWriter);
+        assert!(write_object(&mut writer).is_err());
+        let error = writer.take_error().unwrap();
+        assert_eq!(error.to_string(), "disk full");
+        assert!(writer.take_error().is_none());
+        assert_eq!(writer.bytes_written(), 0);
+    }
+
+    #[test]
+    fn test_patch() -> Result<(), std::fmt::Error> {
+        let mut cursor = Cursor::new(b"prefix".to_vec());
+        cursor.set_position(6);
+        let mut writer = IoWriter::new(cursor);
+        let mut object_writer = write_object(&mut writer)?;
+        let count = object_writer.reserve_member("count", 6)?;
+        object_writer.member("items", &[1u8, 2u8][..])?;
+        object_writer.end()?;
+        count.fill(&mut writer, 2u8)?;
+        writer.write_str("\n")?;
+
+        assert!(writer.patch(100, "x").is_err());
+        assert_eq!(
+            writer.into_writer().into_inner(),
+            b"prefix{\"count\":2     ,\"items\":[1,2]}\n"
+        );
+
+        Ok(())
+    }
+}
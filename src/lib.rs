@@ -1,3 +1,4 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(missing_docs)]
 
 //!
@@ -44,9 +45,11 @@
 //! assert_eq!(to_json_string(&strings[..]), "[\"a\",\"b\",\"c\",\"d\"]");
 //!
 //! // Hash-maps:
+//! # #[cfg(feature = "std")] {
 //! let mut map = std::collections::HashMap::<String,String>::new();
 //! map.insert("Hello".to_owned(), "World".to_owned());
 //! assert_eq!(to_json_string(&map), "{\"Hello\":\"World\"}");
+//! # }
 //!
 //! // Objects:
 //! let mut object_str = String::new();
@@ -99,9 +102,31 @@
 //! }
 //! ```
 //!
+#![cfg_attr(
+    feature = "std",
+    doc = r#"
+Alternatively wrap the file in an [`IoWriter`] to write to it directly.
+[`write_json_file_atomic`] replaces a file only after the new content was written completely.
+[`ArrayFileAppender`] appends values to an array in an existing file.
+Use a [`NdjsonWriter`] to write one record per line (or a [`RotatingNdjsonWriter`] to split them into
+several files), a [`JsonSeqWriter`] for JSON text sequences
+or a [`SseWriter`] for server-sent events. A [`FramedWriter`] prefixes every message with its length.
+"#
+)]
+//!
+//! With the `gzip` or `zstd` feature, the output can be compressed on the fly
+//! with a `GzipWriter` or `ZstdWriter`, which finish the compressed stream when the top-level object or array is closed.
+//! With the `tokio` feature, an `AsyncIoWriter` writes to a `tokio::io::AsyncWrite`.
+//! With the `bytes` feature, a `bytes::BytesMut` can be used as writer, a `BytesWriter` writes to
+//! other byte buffers like `Vec<u8>` and `to_json_bytes` returns `bytes::Bytes`.
+//! With the `rayon` feature, `JSONArrayWriter::par_values` serializes large slices in parallel.
+//! With the `stream` feature, a `JsonArrayStream` turns a stream of values into a stream of
+//! `bytes::Bytes` chunks for HTTP response bodies.
+//!
 //! # Limitations
 //!
 //! Because there is no intermediate representations, all values must be written in the order they appear in the JSON output.
+//! Use [`JSONObjectWriter::deferred`] to write members out of order into a separate buffer.
 //! The Borrow checker ensures sub-objects are closed before anything else can be written after them.
 //! ```compile_fail
 //! use json_writer::write_object;
@@ -132,8 +157,93 @@
 //! assert_eq!(&object_str, "{\"number\":42,\"number\":43}");
 //! ```
 //!
+//! Wrap the buffer in a [`CheckedWriter`] to detect duplicate keys or to limit the size of the output.
+//!
+//! # `no_std` support
+//!
+//! The `std` feature is enabled by default. Without it, the crate is `no_std` and only requires `alloc`.
+//! The writers for `std::io::Write` and files as well as the implementation for `HashMap`
+//! require `std`. Enable the `hashbrown` feature to serialize a `hashbrown::HashMap` instead.
+//!
 
-type WriteResult = Result<(), std::fmt::Error>;
+extern crate alloc;
+
+#[cfg(feature = "tokio")]
+mod async_io;
+mod budget;
+#[cfg(feature = "bytes")]
+mod buffer;
+mod cap;
+mod checked;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+mod compress;
+mod count;
+mod deferred;
+#[cfg(feature = "std")]
+mod file;
+mod fixed;
+#[cfg(feature = "std")]
+mod framed;
+mod hash;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "std")]
+mod ndjson;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod patch;
+mod rewind;
+#[cfg(feature = "std")]
+mod rotate;
+mod scan;
+mod seq;
+#[cfg(feature = "std")]
+mod sse;
+#[cfg(feature = "stream")]
+mod stream;
+mod tee;
+
+#[cfg(feature = "tokio")]
+pub use async_io::AsyncIoWriter;
+pub use budget::BudgetWriter;
+#[cfg(feature = "bytes")]
+pub use buffer::{to_json_bytes, BytesWriter};
+pub use cap::{ArrayCap, CappedArrayWriter, CappedObjectWriter, OmittedSummary};
+pub use checked::{CheckError, CheckedWriter};
+#[cfg(feature = "gzip")]
+pub use compress::GzipWriter;
+#[cfg(feature = "zstd")]
+pub use compress::ZstdWriter;
+pub use count::{json_len, CountingSink};
+pub use deferred::DeferredSection;
+#[cfg(feature = "std")]
+pub use file::{write_json_file_atomic, ArrayFileAppender};
+pub use fixed::{to_json_slice, CapacityError, FixedBuffer};
+#[cfg(feature = "std")]
+pub use framed::{FrameWriter, FramedWriter, LengthPrefix};
+pub use hash::{Digest, Fnv1a, HashingWriter};
+#[cfg(feature = "std")]
+pub use io::IoWriter;
+#[cfg(feature = "std")]
+pub use ndjson::{NdjsonWriter, RecordWriter};
+#[cfg(feature = "rayon")]
+pub use parallel::ParallelValue;
+pub use patch::{InsertableWriter, PatchableWriter, Placeholder};
+pub use rewind::{Checkpoint, RewindableWriter};
+#[cfg(feature = "std")]
+pub use rotate::RotatingNdjsonWriter;
+pub use seq::JsonSeqWriter;
+#[cfg(feature = "std")]
+pub use sse::{DataWriter, SseFields, SseWriter};
+#[cfg(feature = "stream")]
+pub use stream::{IterStream, JsonArrayStream};
+pub use tee::Tee;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use cap::{write_capped_members, write_capped_values};
+
+type WriteResult = Result<(), core::fmt::Error>;
 
 ///
 /// Helper for appending a JSON object to the borrowed writer.
@@ -143,7 +253,7 @@ type WriteResult = Result<(), std::fmt::Error>;
 /// Appends '{' on creation.
 /// Appends '}' when closed.
 ///
-pub struct JSONObjectWriter<'a, W: std::fmt::Write> {
+pub struct JSONObjectWriter<'a, W: core::fmt::Write> {
     writer: &'a mut W,
     empty: bool,
 }
@@ -156,7 +266,7 @@ pub struct JSONObjectWriter<'a, W: std::fmt::Write> {
 /// Appends '[' on creation.
 /// Appends ']' when closed.
 ///
-pub struct JSONArrayWriter<'a, W: std::fmt::Write> {
+pub struct JSONArrayWriter<'a, W: core::fmt::Write> {
     writer: &'a mut W,
     empty: bool,
 }
@@ -169,14 +279,14 @@ pub struct JSONArrayWriter<'a, W: std::fmt::Write> {
 #[derive(Debug, Copy, Clone)]
 pub struct Null;
 
-impl<'a, W: std::fmt::Write> JSONObjectWriter<'a, W> {
+impl<'a, W: core::fmt::Write> JSONObjectWriter<'a, W> {
     ///
     /// Creates a new JSONObjectWriter that writes to the given buffer.
     ///
     /// Writes '{' to the buffer immediately.
     ///
     #[inline(always)]
-    fn new(buffer: &'a mut W) -> Result<JSONObjectWriter<'a, W>, std::fmt::Error> {
+    fn new(buffer: &'a mut W) -> Result<JSONObjectWriter<'a, W>, core::fmt::Error> {
         buffer.write_char('{')?;
         Ok(JSONObjectWriter {
             writer: buffer,
@@ -191,7 +301,10 @@ impl<'a, W: std::fmt::Write> JSONObjectWriter<'a, W> {
     /// The ',' is only written if this is the first member.
     ///
     #[inline(always)]
-    pub fn object<'b>(&'b mut self, key: &str) -> Result<JSONObjectWriter<'b, W>, std::fmt::Error> {
+    pub fn object<'b>(
+        &'b mut self,
+        key: &str,
+    ) -> Result<JSONObjectWriter<'b, W>, core::fmt::Error> {
         self.write_key(key)?;
         JSONObjectWriter::new(self.writer)
     }
@@ -203,7 +316,7 @@ impl<'a, W: std::fmt::Write> JSONObjectWriter<'a, W> {
     /// The ',' is only written if this is the first member.
     ///
     #[inline(always)]
-    pub fn array<'b>(&'b mut self, key: &str) -> Result<JSONArrayWriter<'b, W>, std::fmt::Error> {
+    pub fn array<'b>(&'b mut self, key: &str) -> Result<JSONArrayWriter<'b, W>, core::fmt::Error> {
         self.write_key(key)?;
         JSONArrayWriter::new(self.writer)
     }
@@ -280,6 +393,7 @@ impl<'a, W: std::fmt::Write> JSONObjectWriter<'a, W> {
     /// Consumes this writer.
     ///
     /// Writes '}' to the encapsulated writer.
+    /// Pending [`DeferredSection`]s are discarded, use [`JSONObjectWriter::end_with_deferred`] to write them.
     ///
     /// Prefer using this method instead of dropping the writer directly because
     /// dropping ignores any errors the encapsulated writer might produce.
@@ -288,7 +402,7 @@ impl<'a, W: std::fmt::Write> JSONObjectWriter<'a, W> {
     pub fn end(self) -> WriteResult {
         let result = self.writer.write_char('}');
         // make sure we don't write it twice
-        std::mem::forget(self);
+        core::mem::forget(self);
         result
     }
 }
@@ -296,21 +410,21 @@ impl<'a, W: std::fmt::Write> JSONObjectWriter<'a, W> {
 ///
 /// Dropping ignores any errors that might occur in the encapsulated writer.
 ///
-impl<W: std::fmt::Write> Drop for JSONObjectWriter<'_, W> {
+impl<W: core::fmt::Write> Drop for JSONObjectWriter<'_, W> {
     #[inline(always)]
     fn drop(&mut self) {
         let _ignored = self.writer.write_char('}');
     }
 }
 
-impl<'a, W: std::fmt::Write> JSONArrayWriter<'a, W> {
+impl<'a, W: core::fmt::Write> JSONArrayWriter<'a, W> {
     ///
     /// Creates a new JSONArrayWriter that writes to the given buffer.
     ///
     /// Writes '[' to the buffer immediately.
     ///
     #[inline(always)]
-    fn new(buffer: &'a mut W) -> Result<JSONArrayWriter<'a, W>, std::fmt::Error> {
+    fn new(buffer: &'a mut W) -> Result<JSONArrayWriter<'a, W>, core::fmt::Error> {
         buffer.write_char('[')?;
         Ok(JSONArrayWriter {
             writer: buffer,
@@ -318,13 +432,41 @@ impl<'a, W: std::fmt::Write> JSONArrayWriter<'a, W> {
         })
     }
 
+    ///
+    /// Creates a JSONArrayWriter that continues an array whose '[' was already written.
+    ///
+    /// `empty` must be false if the array already contains values.
+    ///
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub(crate) fn resume(buffer: &'a mut W, empty: bool) -> JSONArrayWriter<'a, W> {
+        JSONArrayWriter {
+            writer: buffer,
+            empty,
+        }
+    }
+
+    ///
+    /// Consumes this writer without closing the array.
+    ///
+    /// Returns the `empty` flag to pass to [`JSONArrayWriter::resume`] to continue the array.
+    ///
+    #[cfg(feature = "stream")]
+    #[inline(always)]
+    pub(crate) fn suspend(self) -> bool {
+        let empty = self.empty;
+        // make sure ']' is not written
+        core::mem::forget(self);
+        empty
+    }
+
     ///
     /// Starts writing a nested object as array entry.
     ///
     /// Writes '{' and returns a JSONObjectWriter
     ///
     #[inline(always)]
-    pub fn object(&mut self) -> Result<JSONObjectWriter<'_, W>, std::fmt::Error> {
+    pub fn object(&mut self) -> Result<JSONObjectWriter<'_, W>, core::fmt::Error> {
         self.write_comma()?;
         JSONObjectWriter::new(self.writer)
     }
@@ -335,7 +477,7 @@ impl<'a, W: std::fmt::Write> JSONArrayWriter<'a, W> {
     /// Writes '[' and returns a JSONArrayWriter
     ///
     #[inline(always)]
-    pub fn array(&mut self) -> Result<JSONArrayWriter<'_, W>, std::fmt::Error> {
+    pub fn array(&mut self) -> Result<JSONArrayWriter<'_, W>, core::fmt::Error> {
         self.write_comma()?;
         JSONArrayWriter::new(self.writer)
     }
@@ -400,7 +542,7 @@ impl<'a, W: std::fmt::Write> JSONArrayWriter<'a, W> {
     pub fn end(self) -> WriteResult {
         let result = self.writer.write_char(']');
         // make sure we don't write it twice
-        std::mem::forget(self);
+        core::mem::forget(self);
         result
     }
 }
@@ -408,7 +550,7 @@ impl<'a, W: std::fmt::Write> JSONArrayWriter<'a, W> {
 ///
 /// Dropping ignores any errors that might occur in the encapsulated writer.
 ///
-impl<W: std::fmt::Write> Drop for JSONArrayWriter<'_, W> {
+impl<W: core::fmt::Write> Drop for JSONArrayWriter<'_, W> {
     #[inline(always)]
     fn drop(&mut self) {
         let _ignored = self.writer.write_char(']');
@@ -422,7 +564,140 @@ pub trait JSONWriterValue {
     ///
     /// Appends a JSON representation of self to the output buffer
     ///
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult;
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult;
+
+    ///
+    /// Appends a JSON representation of self to the output buffer, writing at most
+    /// [`ArrayCap::max_values`] values to each array.
+    ///
+    /// The default implementation ignores the cap.
+    /// Types that contain arrays should override it and pass the cap on.
+    ///
+    #[inline(always)]
+    fn write_json_capped<W: core::fmt::Write>(
+        self,
+        output_buffer: &mut W,
+        cap: ArrayCap,
+    ) -> WriteResult
+    where
+        Self: Sized,
+    {
+        let _ = cap;
+        self.write_json(output_buffer)
+    }
+
+    ///
+    /// Returns the bounds on the number of bytes [`JSONWriterValue::write_json`] appends.
+    ///
+    /// The lower bound is used to reserve capacity up front, e.g. by [`to_json_string`].
+    /// The upper bound is `None` if it is unknown. The default implementation returns
+    /// [`JSONWriterValue::size_bounds`], or `(0, None)` if there are none.
+    ///
+    /// Size hints must be cheap. Collections of values with [`JSONWriterValue::size_bounds`] compute them
+    /// from their length, other collections add up the size hints of their values, e.g. the lengths of strings.
+    ///
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        Self::size_bounds().unwrap_or((0, None))
+    }
+
+    ///
+    /// Returns the bounds on the number of bytes that hold for every value of this type, if there are any.
+    ///
+    /// Used to compute the size hints of collections of this type arithmetically.
+    /// The default implementation returns `None`.
+    ///
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        None
+    }
+}
+
+///
+/// Returns the size hint of a string with `len` bytes before escaping.
+///
+/// Every byte is escaped as `\u00XX` in the worst case.
+///
+#[inline(always)]
+fn string_size_hint(len: usize) -> (usize, Option<usize>) {
+    (
+        len + 2,
+        len.checked_mul(6).and_then(|max| max.checked_add(2)),
+    )
+}
+
+///
+/// Returns the size hint of an array with the given values.
+///
+/// The values are only visited if their type has no [`JSONWriterValue::size_bounds`].
+///
+#[inline]
+fn array_size_hint<T, I>(values: I) -> (usize, Option<usize>)
+where
+    T: JSONWriterValue,
+    I: ExactSizeIterator<Item = T>,
+{
+    let len = values.len();
+    let separators = len.saturating_sub(1) + 2;
+    match T::size_bounds() {
+        Some((value_lower, value_upper)) => (
+            value_lower.saturating_mul(len).saturating_add(separators),
+            value_upper
+                .and_then(|upper| upper.checked_mul(len))
+                .and_then(|upper| upper.checked_add(separators)),
+        ),
+        None => add_size_hints(values.map(|value| value.size_hint()), separators),
+    }
+}
+
+///
+/// Returns the size hint of an object with the given members.
+///
+#[inline]
+fn object_size_hint<K, T, I>(members: I) -> (usize, Option<usize>)
+where
+    K: AsRef<str>,
+    T: JSONWriterValue,
+    I: ExactSizeIterator<Item = (K, T)>,
+{
+    let separators = members.len().saturating_sub(1) + 2;
+    let member_hints = members.map(|(key, value)| {
+        let (key_lower, key_upper) = string_size_hint(key.as_ref().len());
+        let (value_lower, value_upper) = match T::size_bounds() {
+            Some(bounds) => bounds,
+            None => value.size_hint(),
+        };
+        // one byte for the colon
+        (
+            key_lower.saturating_add(value_lower).saturating_add(1),
+            key_upper
+                .zip(value_upper)
+                .and_then(|(key_upper, value_upper)| key_upper.checked_add(value_upper))
+                .and_then(|upper| upper.checked_add(1)),
+        )
+    });
+    add_size_hints(member_hints, separators)
+}
+
+///
+/// Adds up the size hints of the entries of an array or object and its `separators`.
+///
+#[inline]
+fn add_size_hints<I>(hints: I, separators: usize) -> (usize, Option<usize>)
+where
+    I: Iterator<Item = (usize, Option<usize>)>,
+{
+    hints.fold(
+        (separators, Some(separators)),
+        |(lower, upper), (entry_lower, entry_upper)| {
+            (
+                lower.saturating_add(entry_lower),
+                upper
+                    .zip(entry_upper)
+                    .and_then(|(upper, entry_upper)| upper.checked_add(entry_upper)),
+            )
+        },
+    )
 }
 
 ///
@@ -430,9 +705,14 @@ pub trait JSONWriterValue {
 ///
 impl JSONWriterValue for &str {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         write_string(output_buffer, self)
     }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        string_size_hint(self.len())
+    }
 }
 
 ///
@@ -440,9 +720,14 @@ impl JSONWriterValue for &str {
 ///
 impl JSONWriterValue for &String {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         write_string(output_buffer, self)
     }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        string_size_hint(self.len())
+    }
 }
 
 ///
@@ -453,9 +738,14 @@ impl JSONWriterValue for &String {
 ///
 impl JSONWriterValue for f64 {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         write_float(output_buffer, self)
     }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        Some((1, Some(24)))
+    }
 }
 
 ///
@@ -466,9 +756,14 @@ impl JSONWriterValue for f64 {
 ///
 impl JSONWriterValue for f32 {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         write_float(output_buffer, self as f64)
     }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        Some((1, Some(24)))
+    }
 }
 
 ///
@@ -476,10 +771,15 @@ impl JSONWriterValue for f32 {
 ///
 impl JSONWriterValue for u32 {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         let mut buf = itoa::Buffer::new();
         output_buffer.write_str(buf.format(self))
     }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        Some((1, Some(10)))
+    }
 }
 
 ///
@@ -487,10 +787,15 @@ impl JSONWriterValue for u32 {
 ///
 impl JSONWriterValue for i32 {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         let mut buf = itoa::Buffer::new();
         output_buffer.write_str(buf.format(self))
     }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        Some((1, Some(11)))
+    }
 }
 
 ///
@@ -498,10 +803,15 @@ impl JSONWriterValue for i32 {
 ///
 impl JSONWriterValue for u16 {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         let mut buf = itoa::Buffer::new();
         output_buffer.write_str(buf.format(self))
     }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        Some((1, Some(5)))
+    }
 }
 
 ///
@@ -509,10 +819,15 @@ impl JSONWriterValue for u16 {
 ///
 impl JSONWriterValue for i16 {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         let mut buf = itoa::Buffer::new();
         output_buffer.write_str(buf.format(self))
     }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        Some((1, Some(6)))
+    }
 }
 
 ///
@@ -520,10 +835,15 @@ impl JSONWriterValue for i16 {
 ///
 impl JSONWriterValue for u8 {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         let mut buf = itoa::Buffer::new();
         output_buffer.write_str(buf.format(self))
     }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        Some((1, Some(3)))
+    }
 }
 
 ///
@@ -531,10 +851,15 @@ impl JSONWriterValue for u8 {
 ///
 impl JSONWriterValue for i8 {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         let mut buf = itoa::Buffer::new();
         output_buffer.write_str(buf.format(self))
     }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        Some((1, Some(4)))
+    }
 }
 
 ///
@@ -542,9 +867,14 @@ impl JSONWriterValue for i8 {
 ///
 impl JSONWriterValue for bool {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         output_buffer.write_str(if self { "true" } else { "false" })
     }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        Some((4, Some(5)))
+    }
 }
 
 ///
@@ -552,16 +882,40 @@ impl JSONWriterValue for bool {
 ///
 impl JSONWriterValue for Null {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         output_buffer.write_str("null")
     }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        Some((4, Some(4)))
+    }
 }
 
 impl<T: JSONWriterValue + Copy> JSONWriterValue for &T {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         (*self).write_json(output_buffer)
     }
+
+    #[inline(always)]
+    fn write_json_capped<W: core::fmt::Write>(
+        self,
+        output_buffer: &mut W,
+        cap: ArrayCap,
+    ) -> WriteResult {
+        (*self).write_json_capped(output_buffer, cap)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (**self).size_hint()
+    }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        T::size_bounds()
+    }
 }
 
 // impl JSONWriterValue for serde_json::value::Value::Null {
@@ -576,12 +930,42 @@ impl<T: JSONWriterValue + Copy> JSONWriterValue for &T {
 ///
 impl<T: JSONWriterValue> JSONWriterValue for Option<T> {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         match self {
             None => output_buffer.write_str("null"),
             Some(value) => value.write_json(output_buffer),
         }
     }
+
+    #[inline(always)]
+    fn write_json_capped<W: core::fmt::Write>(
+        self,
+        output_buffer: &mut W,
+        cap: ArrayCap,
+    ) -> WriteResult {
+        match self {
+            None => output_buffer.write_str("null"),
+            Some(value) => value.write_json_capped(output_buffer, cap),
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            None => (4, Some(4)),
+            Some(value) => value.size_hint(),
+        }
+    }
+
+    #[inline(always)]
+    fn size_bounds() -> Option<(usize, Option<usize>)> {
+        T::size_bounds().map(|(lower, upper)| {
+            (
+                usize::min(lower, 4),
+                upper.map(|upper| usize::max(upper, 4)),
+            )
+        })
+    }
 }
 
 ///
@@ -592,9 +976,23 @@ where
     for<'b> &'b Item: JSONWriterValue,
 {
     #[inline(always)]
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         (&self[..]).write_json(output_buffer)
     }
+
+    #[inline(always)]
+    fn write_json_capped<W: core::fmt::Write>(
+        self,
+        output_buffer: &mut W,
+        cap: ArrayCap,
+    ) -> WriteResult {
+        (&self[..]).write_json_capped(output_buffer, cap)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (&self[..]).size_hint()
+    }
 }
 
 ///
@@ -604,55 +1002,129 @@ impl<Item> JSONWriterValue for &[Item]
 where
     for<'b> &'b Item: JSONWriterValue,
 {
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         let mut array = JSONArrayWriter::new(output_buffer)?;
         for item in self.iter() {
             array.value(item)?;
         }
         Ok(())
     }
+
+    fn write_json_capped<W: core::fmt::Write>(
+        self,
+        output_buffer: &mut W,
+        cap: ArrayCap,
+    ) -> WriteResult {
+        write_capped_values(output_buffer, self.iter(), self.len(), cap)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        array_size_hint(self.iter())
+    }
 }
 
 ///
 /// Serializes as a JSON object.
 ///
+#[cfg(feature = "std")]
 impl<Key: AsRef<str>, Item> JSONWriterValue for &std::collections::HashMap<Key, Item>
 where
     for<'b> &'b Item: JSONWriterValue,
 {
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+        let mut obj = JSONObjectWriter::new(output_buffer)?;
+        for (key, value) in self.iter() {
+            obj.member(key.as_ref(), value)?;
+        }
+        Ok(())
+    }
+
+    fn write_json_capped<W: core::fmt::Write>(
+        self,
+        output_buffer: &mut W,
+        cap: ArrayCap,
+    ) -> WriteResult {
+        write_capped_members(output_buffer, self.iter(), cap)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        object_size_hint(self.iter())
+    }
+}
+
+///
+/// Serializes as a JSON object.
+///
+/// Requires the `hashbrown` feature.
+///
+#[cfg(feature = "hashbrown")]
+impl<Key: AsRef<str>, Item, S> JSONWriterValue for &hashbrown::HashMap<Key, Item, S>
+where
+    for<'b> &'b Item: JSONWriterValue,
+{
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         let mut obj = JSONObjectWriter::new(output_buffer)?;
         for (key, value) in self.iter() {
             obj.member(key.as_ref(), value)?;
         }
         Ok(())
     }
+
+    fn write_json_capped<W: core::fmt::Write>(
+        self,
+        output_buffer: &mut W,
+        cap: ArrayCap,
+    ) -> WriteResult {
+        write_capped_members(output_buffer, self.iter(), cap)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        object_size_hint(self.iter())
+    }
 }
 
 ///
 /// Serializes as a JSON object.
 ///
-impl<Key: AsRef<str>, Item> JSONWriterValue for &std::collections::BTreeMap<Key, Item>
+impl<Key: AsRef<str>, Item> JSONWriterValue for &alloc::collections::BTreeMap<Key, Item>
 where
     for<'b> &'b Item: JSONWriterValue,
 {
-    fn write_json<W: std::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
+    fn write_json<W: core::fmt::Write>(self, output_buffer: &mut W) -> WriteResult {
         let mut obj = JSONObjectWriter::new(output_buffer)?;
         for (key, value) in self.iter() {
             obj.member(key.as_ref(), value)?;
         }
         Ok(())
     }
+
+    fn write_json_capped<W: core::fmt::Write>(
+        self,
+        output_buffer: &mut W,
+        cap: ArrayCap,
+    ) -> WriteResult {
+        write_capped_members(output_buffer, self.iter(), cap)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        object_size_hint(self.iter())
+    }
 }
 
 ///
 /// Serializes the given `value` to JSON.
 ///
 /// This is the same as calling [`write_value`] with an empty [`String`] as buffer.
+/// Use [`json_len`] to compute the length of the result without allocating
+/// and [`to_json_slice`] to write it to a fixed size buffer.
 ///
 #[inline]
 pub fn to_json_string<T: JSONWriterValue>(value: T) -> String {
-    let mut result = String::new();
+    let mut result = String::with_capacity(value.size_hint().0);
     // String never returns an error in it's Write implementation.
     value.write_json(&mut result).unwrap();
     result
@@ -661,7 +1133,7 @@ pub fn to_json_string<T: JSONWriterValue>(value: T) -> String {
 ///
 /// Writes the `value` as JSON to the `output_buffer`.
 ///
-pub fn write_value<W: std::fmt::Write, T: JSONWriterValue>(
+pub fn write_value<W: core::fmt::Write, T: JSONWriterValue>(
     output_buffer: &mut W,
     value: T,
 ) -> WriteResult {
@@ -676,9 +1148,9 @@ pub fn write_value<W: std::fmt::Write, T: JSONWriterValue>(
 ///
 /// Writes '{' to the buffer immediately.
 ///
-pub fn write_object<W: std::fmt::Write>(
+pub fn write_object<W: core::fmt::Write>(
     output_buffer: &mut W,
-) -> Result<JSONObjectWriter<'_, W>, std::fmt::Error> {
+) -> Result<JSONObjectWriter<'_, W>, core::fmt::Error> {
     JSONObjectWriter::new(output_buffer)
 }
 
@@ -690,9 +1162,9 @@ pub fn write_object<W: std::fmt::Write>(
 ///
 /// Writes '[' to the buffer immediately.
 ///
-pub fn write_array<W: std::fmt::Write>(
+pub fn write_array<W: core::fmt::Write>(
     output_buffer: &mut W,
-) -> Result<JSONArrayWriter<'_, W>, std::fmt::Error> {
+) -> Result<JSONArrayWriter<'_, W>, core::fmt::Error> {
     JSONArrayWriter::new(output_buffer)
 }
 
@@ -700,7 +1172,7 @@ pub fn write_array<W: std::fmt::Write>(
 /// Quotes and escapes `input` and appends result to `output_buffer`.
 ///
 #[inline(never)]
-fn write_string<W: std::fmt::Write>(output_buffer: &mut W, input: &str) -> WriteResult {
+pub(crate) fn write_string<W: core::fmt::Write>(output_buffer: &mut W, input: &str) -> WriteResult {
     output_buffer.write_char('"')?;
     write_part_of_string_impl(output_buffer, input)?;
     output_buffer.write_char('"')?;
@@ -720,7 +1192,10 @@ fn write_string<W: std::fmt::Write>(output_buffer: &mut W, input: &str) -> Write
 /// including the quotes enclosing it.
 ///
 #[inline(never)]
-pub fn write_part_of_string<W: std::fmt::Write>(output_buffer: &mut W, input: &str) -> WriteResult {
+pub fn write_part_of_string<W: core::fmt::Write>(
+    output_buffer: &mut W,
+    input: &str,
+) -> WriteResult {
     write_part_of_string_impl(output_buffer, input)
 }
 
@@ -766,7 +1241,7 @@ static HEX: [u8; 16] = *b"0123456789ABCDEF";
 /// Escapes and append part of string
 ///
 #[inline(always)]
-fn write_part_of_string_impl<W: std::fmt::Write>(
+fn write_part_of_string_impl<W: core::fmt::Write>(
     output_buffer: &mut W,
     input: &str,
 ) -> WriteResult {
@@ -797,11 +1272,11 @@ fn write_part_of_string_impl<W: std::fmt::Write>(
                     HEX[(cur_byte & 0xF) as usize],
                 ];
                 // Checks can be ommitted here: We know bytes is a valid utf-8 string (see above)
-                output_buffer.write_str(unsafe { std::str::from_utf8_unchecked(&bytes) })?;
+                output_buffer.write_str(unsafe { core::str::from_utf8_unchecked(&bytes) })?;
             } else {
                 let bytes: [u8; 2] = [b'\\', replacement];
                 // Checks can be ommitted here: We know bytes is a valid utf-8 string, because the replacement table only contains characters smaller than 128
-                output_buffer.write_str(unsafe { std::str::from_utf8_unchecked(&bytes) })?;
+                output_buffer.write_str(unsafe { core::str::from_utf8_unchecked(&bytes) })?;
             }
             num_bytes_written = index + 1;
         }
@@ -821,7 +1296,7 @@ fn write_part_of_string_impl<W: std::fmt::Write>(
 /// If value is NaN or infinity, then the string "null" is appended to buffer (without the quotes)
 ///
 #[inline(never)]
-fn write_float<W: std::fmt::Write>(output_buffer: &mut W, value: f64) -> WriteResult {
+fn write_float<W: core::fmt::Write>(output_buffer: &mut W, value: f64) -> WriteResult {
     if !value.is_finite() {
         // JSON does not allow infinite or nan values. In browsers JSON.stringify(Number.NaN) = "null"
         output_buffer.write_str("null")?;
@@ -859,7 +1334,20 @@ fn write_float<W: std::fmt::Write>(output_buffer: &mut W, value: f64) -> WriteRe
 //     return usize::MAX;
 // }
 
+///
+/// Returns an empty directory for the files of the test `name`.
+///
+#[cfg(all(test, feature = "std"))]
+pub(crate) fn test_directory(name: &str) -> std::path::PathBuf {
+    let directory =
+        std::env::temp_dir().join(format!("json-writer-{}-{}", name, std::process::id()));
+    let _ignored = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
 #[cfg(test)]
+#[allow(clippy::legacy_numeric_constants)] // test_numbers uses the std::f64 constants
 mod tests {
     use super::*;
     use std::io::Write;
@@ -902,6 +1390,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn test_object() -> Result<(), std::fmt::Error> {
         let mut map = std::collections::HashMap::<String, String>::new();
@@ -1019,9 +1508,12 @@ mod tests {
         assert_eq!(to_json_string(&strings[..]), "[\"a\",\"b\",\"c\",\"d\"]");
 
         // Hash-maps:
-        let mut map = std::collections::HashMap::<String, String>::new();
-        map.insert("Hello".to_owned(), "World".to_owned());
-        assert_eq!(to_json_string(&map), "{\"Hello\":\"World\"}");
+        #[cfg(feature = "std")]
+        {
+            let mut map = std::collections::HashMap::<String, String>::new();
+            map.insert("Hello".to_owned(), "World".to_owned());
+            assert_eq!(to_json_string(&map), "{\"Hello\":\"World\"}");
+        }
 
         // Objects:
         let mut object_str = String::new();
@@ -1115,6 +1607,65 @@ mod tests {
         Ok(())
     }
 
+    fn assert_size_hint<T: JSONWriterValue + Copy>(value: T) {
+        let (lower, upper) = value.size_hint();
+        let len = to_json_string(value).len();
+        assert!(lower <= len, "{} > {}", lower, len);
+        assert!(
+            upper.is_none_or(|upper| len <= upper),
+            "{:?} < {}",
+            upper,
+            len
+        );
+    }
+
+    #[test]
+    fn test_size_hint() {
+        assert_size_hint(0u8);
+        assert_size_hint(i8::MIN);
+        assert_size_hint(i16::MIN);
+        assert_size_hint(u16::MAX);
+        assert_size_hint(i32::MIN);
+        assert_size_hint(u32::MAX);
+        assert_size_hint(-1.2345678901234567e-300f64);
+        assert_size_hint(f32::MIN);
+        assert_size_hint(f64::NAN);
+        assert_size_hint(false);
+        assert_size_hint(Null);
+        assert_size_hint(Some(1u8));
+        assert_size_hint("");
+        assert_size_hint("\u{1}\u{1f}\"äöü");
+
+        let strings = vec!["a".to_owned(), "\n".to_owned()];
+        assert_size_hint(&strings);
+
+        // collections compute their hints from their length
+        assert_eq!((&[true, false][..]).size_hint(), (11, Some(13)));
+        assert_eq!((&[Some(1u8), None][..]).size_hint(), (5, Some(11)));
+        assert_eq!((&strings).size_hint(), (9, Some(19)));
+        assert_size_hint(&[Some(100u8), None][..]);
+        assert_size_hint(&Vec::<u8>::new());
+        #[cfg(feature = "std")]
+        {
+            let mut map = std::collections::HashMap::new();
+            map.insert("a\tb", vec![1.5f64, 2.0]);
+            map.insert("c", vec![]);
+            assert_size_hint(&map);
+        }
+        let numbers: Vec<u32> = (0..100).collect();
+        assert_size_hint(&numbers);
+    }
+
+    #[cfg(feature = "hashbrown")]
+    #[test]
+    fn test_hashbrown() {
+        let hasher = std::collections::hash_map::RandomState::new();
+        let mut map = hashbrown::HashMap::with_hasher(hasher);
+        map.insert("a", vec![1u8]);
+        assert_eq!(to_json_string(&map), "{\"a\":[1]}");
+        assert_size_hint(&map);
+    }
+
     #[test]
     fn test_control_characters() -> Result<(), std::fmt::Error> {
         // all ascii characters 0x00 <= c < 0x20 must be escaped
@@ -0,0 +1,242 @@
+use crate::{
+    write_array, write_object, IoWriter, JSONArrayWriter, JSONObjectWriter, JSONWriterValue,
+    WriteResult,
+};
+
+///
+/// Writer for newline-delimited JSON (also known as JSON Lines).
+///
+/// Every record is written as one line, terminated by `\n`.
+/// Records are buffered and only written once they are complete, failed records are not written at all.
+///
+/// ```
+/// use json_writer::NdjsonWriter;
+///
+/// let mut writer = NdjsonWriter::new(Vec::<u8>::new());
+/// for level in ["info", "warn"] {
+///     writer
+///         .object(|record| {
+///             record.member("level", level)?;
+///             record.member("message", "line 1\nline 2")
+///         })
+///         .unwrap();
+/// }
+///
+/// assert_eq!(writer.records(), 2);
+/// assert_eq!(
+///     writer.into_writer(),
+///     b"{\"level\":\"info\",\"message\":\"line 1\\nline 2\"}\n\
+///       {\"level\":\"warn\",\"message\":\"line 1\\nline 2\"}\n"
+/// );
+/// ```
+///
+pub struct NdjsonWriter<W: std::io::Write> {
+    sink: IoWriter<W>,
+    buffer: String,
+    records: u64,
+    flush_each_record: bool,
+}
+
+///
+/// Buffer of a single record of a [`NdjsonWriter`].
+///
+/// Fails if a raw newline is written, because it would split the record.
+///
+pub struct RecordWriter<'a> {
+    buffer: &'a mut String,
+    newline: bool,
+}
+
+impl std::fmt::Write for RecordWriter<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        if s.as_bytes().contains(&b'\n') {
+            self.newline = true;
+            return Err(std::fmt::Error);
+        }
+        self.buffer.push_str(s);
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write> NdjsonWriter<W> {
+    ///
+    /// Creates a new NdjsonWriter that writes records to `writer`.
+    ///
+    pub fn new(writer: W) -> NdjsonWriter<W> {
+        NdjsonWriter {
+            sink: IoWriter::new(writer),
+            buffer: String::new(),
+            records: 0,
+            flush_each_record: false,
+        }
+    }
+
+    ///
+    /// Enables or disables flushing the encapsulated writer after every record.
+    ///
+    pub fn flush_each_record(mut self, enabled: bool) -> NdjsonWriter<W> {
+        self.flush_each_record = enabled;
+        self
+    }
+
+    ///
+    /// Returns the number of records written successfully.
+    ///
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    ///
+    /// Returns the number of bytes written to the encapsulated writer.
+    ///
+    pub fn bytes_written(&self) -> usize {
+        self.sink.bytes_written()
+    }
+
+    ///
+    /// Writes a record that is an object.
+    ///
+    /// `f` writes the members, the object is closed afterwards.
+    ///
+    pub fn object<F>(&mut self, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut JSONObjectWriter<'_, RecordWriter<'_>>) -> WriteResult,
+    {
+        self.record(0, |record| {
+            let mut object_writer = write_object(record)?;
+            f(&mut object_writer)?;
+            object_writer.end()
+        })
+    }
+
+    ///
+    /// Writes a record that is an array.
+    ///
+    /// `f` writes the values, the array is closed afterwards.
+    ///
+    pub fn array<F>(&mut self, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut JSONArrayWriter<'_, RecordWriter<'_>>) -> WriteResult,
+    {
+        self.record(0, |record| {
+            let mut array_writer = write_array(record)?;
+            f(&mut array_writer)?;
+            array_writer.end()
+        })
+    }
+
+    ///
+    /// Writes a record that is a single value.
+    ///
+    pub fn value<T: JSONWriterValue>(&mut self, value: T) -> std::io::Result<()> {
+        self.record(value.size_hint().0, |record| value.write_json(record))
+    }
+
+    ///
+    /// Flushes the encapsulated writer.
+    ///
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.sink.flush()
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated writer.
+    ///
+    pub fn writer(&self) -> &W {
+        self.sink.writer()
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated writer.
+    ///
+    pub fn writer_mut(&mut self) -> &mut W {
+        self.sink.writer_mut()
+    }
+
+    ///
+    /// Consumes this writer and returns the encapsulated writer.
+    ///
+    pub fn into_writer(self) -> W {
+        self.sink.into_writer()
+    }
+
+    ///
+    /// Writes one line. The record is buffered and only written if it was completed successfully,
+    /// so that readers never see a failed record.
+    ///
+    /// `size_hint` is the expected length of the record without the line break.
+    ///
+    fn record<F>(&mut self, size_hint: usize, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut RecordWriter<'_>) -> WriteResult,
+    {
+        self.buffer.clear();
+        self.buffer.reserve(size_hint + 1);
+        let mut record = RecordWriter {
+            buffer: &mut self.buffer,
+            newline: false,
+        };
+        if f(&mut record).is_err() {
+            return Err(if record.newline {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "raw newline written to NDJSON record",
+                )
+            } else {
+                format_error()
+            });
+        }
+        self.buffer.push('\n');
+        std::fmt::Write::write_str(&mut self.sink, &self.buffer)
+            .map_err(|_| self.sink.take_error().unwrap_or_else(format_error))?;
+        self.records += 1;
+        if self.flush_each_record {
+            self.sink.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn format_error() -> std::io::Error {
+    std::io::Error::other("formatting NDJSON record failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn test_records() -> std::io::Result<()> {
+        let mut writer = NdjsonWriter::new(Vec::<u8>::new()).flush_each_record(true);
+        writer.array(|array| {
+            array.value(1u8)?;
+            array.value("a")
+        })?;
+        writer.value(42u8)?;
+
+        let error = writer
+            .object(|record| {
+                record.member("a", 1u8)?;
+                record.writer_mut().write_str("\n")
+            })
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        // failed records are not written
+        let error = writer
+            .array(|array| {
+                array.value(1u8)?;
+                Err(std::fmt::Error)
+            })
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Other);
+
+        writer.object(|_| Ok(()))?;
+
+        assert_eq!(writer.records(), 3);
+        assert_eq!(writer.into_writer(), b"[1,\"a\"]\n42\n{}\n");
+
+        Ok(())
+    }
+}
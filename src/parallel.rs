@@ -0,0 +1,185 @@
+use crate::{ArrayCap, CappedArrayWriter, JSONArrayWriter, JSONWriterValue, WriteResult};
+use rayon::prelude::*;
+
+/// Number of values serialized into one buffer by a single task.
+const CHUNK_LEN: usize = 1024;
+
+///
+/// Values that can be serialized by [`JSONArrayWriter::par_values`].
+///
+/// Requires the `rayon` feature.
+///
+/// Implemented for all types that are [`Sync`] and whose references implement [`JSONWriterValue`].
+///
+pub trait ParallelValue: Sync {
+    ///
+    /// Writes a reference to this value, see [`JSONWriterValue::write_json`].
+    ///
+    fn write_json_ref<W: std::fmt::Write>(&self, output_buffer: &mut W) -> WriteResult;
+
+    ///
+    /// Writes a reference to this value, see [`JSONWriterValue::write_json_capped`].
+    ///
+    fn write_json_ref_capped<W: std::fmt::Write>(
+        &self,
+        output_buffer: &mut W,
+        cap: ArrayCap,
+    ) -> WriteResult;
+}
+
+impl<T: Sync> ParallelValue for T
+where
+    for<'b> &'b T: JSONWriterValue,
+{
+    #[inline(always)]
+    fn write_json_ref<W: std::fmt::Write>(&self, output_buffer: &mut W) -> WriteResult {
+        self.write_json(output_buffer)
+    }
+
+    #[inline(always)]
+    fn write_json_ref_capped<W: std::fmt::Write>(
+        &self,
+        output_buffer: &mut W,
+        cap: ArrayCap,
+    ) -> WriteResult {
+        self.write_json_capped(output_buffer, cap)
+    }
+}
+
+impl<W: std::fmt::Write> JSONArrayWriter<'_, W> {
+    ///
+    /// Writes all `values` as array entries, serializing them in parallel.
+    ///
+    /// Requires the `rayon` feature.
+    ///
+    /// The values are split into chunks that are serialized into separate buffers on the rayon thread pool
+    /// and then appended in order. The output is the same as calling [`JSONArrayWriter::value`] for each value.
+    /// Only a limited number of chunks is buffered at a time, regardless of the number of values.
+    ///
+    /// ```
+    /// use json_writer::write_array;
+    ///
+    /// let numbers: Vec<f64> = (0..100000).map(|i| i as f64 / 7.0).collect();
+    /// let mut json = String::new();
+    /// let mut array = write_array(&mut json).unwrap();
+    /// array.par_values(&numbers).unwrap();
+    /// array.end().unwrap();
+    /// ```
+    ///
+    pub fn par_values<T: ParallelValue>(&mut self, values: &[T]) -> WriteResult {
+        write_values(self, values, None)
+    }
+}
+
+impl<W: std::fmt::Write> CappedArrayWriter<'_, W> {
+    ///
+    /// Writes all `values` as array entries, serializing them in parallel.
+    ///
+    /// Requires the `rayon` feature.
+    ///
+    /// Like [`JSONArrayWriter::par_values`], the output is the same as calling
+    /// [`CappedArrayWriter::value`] for each value. Values beyond the [`crate::ArrayCap`]
+    /// are only counted as omitted.
+    ///
+    pub fn par_values<T: ParallelValue>(&mut self, values: &[T]) -> WriteResult {
+        let written = usize::min(values.len(), self.remaining());
+        write_values(&mut self.array, &values[..written], Some(self.cap))?;
+        self.len += written;
+        self.omit(values.len() - written);
+        Ok(())
+    }
+}
+
+///
+/// Writes all `values` to `array`, serializing them in parallel in batches of chunks.
+///
+fn write_values<W: std::fmt::Write, T: ParallelValue>(
+    array: &mut JSONArrayWriter<'_, W>,
+    values: &[T],
+    cap: Option<ArrayCap>,
+) -> WriteResult {
+    let batch_len = CHUNK_LEN * rayon::current_num_threads();
+    for batch in values.chunks(batch_len) {
+        let buffers: Vec<String> = batch
+            .par_chunks(CHUNK_LEN)
+            .map(|chunk| write_chunk(chunk, cap))
+            .collect::<Result<_, _>>()?;
+        for buffer in buffers.iter() {
+            array.write_comma()?;
+            array.writer.write_str(buffer)?;
+        }
+    }
+    Ok(())
+}
+
+///
+/// Writes the values separated by commas, like [`JSONArrayWriter::value`] would.
+///
+fn write_chunk<T: ParallelValue>(
+    values: &[T],
+    cap: Option<ArrayCap>,
+) -> Result<String, std::fmt::Error> {
+    let mut buffer = String::new();
+    for (index, value) in values.iter().enumerate() {
+        if index > 0 {
+            buffer.push(',');
+        }
+        match cap {
+            None => value.write_json_ref(&mut buffer)?,
+            Some(cap) => value.write_json_ref_capped(&mut buffer, cap)?,
+        }
+    }
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{write_array, ArrayCap};
+
+    #[test]
+    fn test_par_values() -> Result<(), std::fmt::Error> {
+        let records: Vec<Vec<String>> = (0..5000)
+            .map(|i| (0..i % 4).map(|j| format!("{}\n{}", i, j)).collect())
+            .collect();
+
+        let mut json = String::new();
+        let mut array = write_array(&mut json)?;
+        array.value(1u8)?;
+        array.par_values(&records)?;
+        array.par_values(&records[..0])?;
+        array.value(2u8)?;
+        array.end()?;
+        let records_json = crate::to_json_string(&records);
+        assert_eq!(
+            json,
+            format!("[1,{},2]", &records_json[1..records_json.len() - 1])
+        );
+
+        for cap in [ArrayCap::new(3), ArrayCap::new(3000)] {
+            let mut parallel = String::new();
+            let mut array = write_array(&mut parallel)?.with_array_cap(cap);
+            array.value(1u8)?;
+            array.par_values(&records)?;
+            array.par_values(&records[..0])?;
+            array.value(2u8)?;
+            array.end()?;
+
+            let mut sequential = String::new();
+            let mut array = write_array(&mut sequential)?.with_array_cap(cap);
+            array.value(1u8)?;
+            records.iter().try_for_each(|record| array.value(record))?;
+            array.value(2u8)?;
+            array.end()?;
+
+            assert_eq!(parallel, sequential);
+        }
+
+        let mut json = String::new();
+        let mut array = write_array(&mut json)?;
+        array.par_values(&records[..2])?;
+        array.end()?;
+        assert_eq!(json, "[[],[\"1\\n0\"]]");
+
+        Ok(())
+    }
+}
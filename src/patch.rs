@@ -0,0 +1,200 @@
+use crate::{JSONObjectWriter, JSONWriterValue, WriteResult};
+use alloc::string::String;
+
+///
+/// Writers that can overwrite output at a previous position.
+///
+/// Required for [`JSONObjectWriter::reserve_member`].
+///
+/// Implemented for `String` and, with the `std` feature, for an `IoWriter` over a seekable writer like a file.
+///
+pub trait PatchableWriter: core::fmt::Write {
+    ///
+    /// Returns the number of bytes written so far.
+    ///
+    fn position(&self) -> usize;
+
+    ///
+    /// Overwrites the bytes starting at `position` with `data`.
+    ///
+    /// Returns an error if the overwritten bytes have not been written or can not be replaced.
+    ///
+    fn patch(&mut self, position: usize, data: &str) -> WriteResult;
+}
+
+///
+/// Writers that can insert output at a previous position.
+///
+/// Required for [`JSONObjectWriter::deferred_in_place`].
+///
+pub trait InsertableWriter: PatchableWriter {
+    ///
+    /// Inserts `data` at `position`, moving everything after it back.
+    ///
+    /// Returns an error if `position` is not part of the output written so far.
+    ///
+    fn insert(&mut self, position: usize, data: &str) -> WriteResult;
+}
+
+impl PatchableWriter for String {
+    #[inline(always)]
+    fn position(&self) -> usize {
+        self.len()
+    }
+
+    #[inline(always)]
+    fn patch(&mut self, position: usize, data: &str) -> WriteResult {
+        let end = position.checked_add(data.len()).ok_or(core::fmt::Error)?;
+        if end > self.len() || !self.is_char_boundary(position) || !self.is_char_boundary(end) {
+            return Err(core::fmt::Error);
+        }
+        self.replace_range(position..end, data);
+        Ok(())
+    }
+}
+
+impl InsertableWriter for String {
+    #[inline(always)]
+    fn insert(&mut self, position: usize, data: &str) -> WriteResult {
+        if !self.is_char_boundary(position) {
+            return Err(core::fmt::Error);
+        }
+        self.insert_str(position, data);
+        Ok(())
+    }
+}
+
+impl<W: PatchableWriter + ?Sized> PatchableWriter for &mut W {
+    #[inline(always)]
+    fn position(&self) -> usize {
+        (**self).position()
+    }
+
+    #[inline(always)]
+    fn patch(&mut self, position: usize, data: &str) -> WriteResult {
+        (**self).patch(position, data)
+    }
+}
+
+impl<W: InsertableWriter + ?Sized> InsertableWriter for &mut W {
+    #[inline(always)]
+    fn insert(&mut self, position: usize, data: &str) -> WriteResult {
+        (**self).insert(position, data)
+    }
+}
+
+/// Written in place of reserved values until they are filled.
+const PLACEHOLDER_VALUE: &str = "null";
+
+///
+/// Reserved space for a member value that is only known later.
+///
+/// Created with [`JSONObjectWriter::reserve_member`].
+///
+#[derive(Debug)]
+#[must_use = "the reserved member is null unless it is filled"]
+pub struct Placeholder {
+    position: usize,
+    width: usize,
+}
+
+impl Placeholder {
+    ///
+    /// Returns the number of bytes reserved for the value.
+    ///
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    ///
+    /// Writes `value` to the reserved space, padded with whitespace.
+    ///
+    /// `writer` must be the writer the placeholder was reserved in.
+    /// Returns an error and leaves the placeholder unchanged if the value is wider than reserved,
+    /// or if the reserved space is not part of `writer`.
+    ///
+    pub fn fill<W: PatchableWriter, T: JSONWriterValue>(
+        self,
+        writer: &mut W,
+        value: T,
+    ) -> WriteResult {
+        let mut json = String::with_capacity(self.width);
+        value.write_json(&mut json)?;
+        if json.len() > self.width {
+            return Err(core::fmt::Error);
+        }
+        json.extend(core::iter::repeat_n(' ', self.width - json.len()));
+        writer.patch(self.position, &json)
+    }
+}
+
+impl<W: PatchableWriter> JSONObjectWriter<'_, W> {
+    ///
+    /// Appends a member whose value is filled in later.
+    ///
+    /// Writes ",\"key\":null" followed by whitespace, so that `width` bytes are reserved for the value.
+    /// At least 4 bytes are reserved, so the output stays valid JSON if the value is never filled.
+    ///
+    /// ```
+    /// use json_writer::write_object;
+    ///
+    /// let mut object_str = String::new();
+    /// let mut object_writer = write_object(&mut object_str).unwrap();
+    /// let count = object_writer.reserve_member("count", 10).unwrap();
+    /// let mut items = object_writer.array("items").unwrap();
+    /// for i in 0..3u8 {
+    ///     items.value(i).unwrap();
+    /// }
+    /// items.end().unwrap();
+    /// count.fill(object_writer.writer_mut(), 3u8).unwrap();
+    /// object_writer.end().unwrap();
+    ///
+    /// assert_eq!(&object_str, "{\"count\":3         ,\"items\":[0,1,2]}");
+    /// ```
+    ///
+    pub fn reserve_member(
+        &mut self,
+        key: &str,
+        width: usize,
+    ) -> Result<Placeholder, core::fmt::Error> {
+        let width = usize::max(width, PLACEHOLDER_VALUE.len());
+        self.write_key(key)?;
+        let position = self.writer.position();
+        self.writer.write_str(PLACEHOLDER_VALUE)?;
+        for _ in PLACEHOLDER_VALUE.len()..width {
+            self.writer.write_char(' ')?;
+        }
+        Ok(Placeholder { position, width })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{to_json_string, write_object};
+
+    #[test]
+    fn test_placeholder() -> Result<(), core::fmt::Error> {
+        let mut buffer = String::new();
+        let mut object_writer = write_object(&mut buffer)?;
+        let unfilled = object_writer.reserve_member("unfilled", 0)?;
+        let name = object_writer.reserve_member("name", 8)?;
+        object_writer.member("ä", "ö")?;
+        object_writer.end()?;
+
+        assert_eq!(unfilled.width(), 4);
+        assert!(name.fill(&mut buffer, "too long").is_err());
+        // the reserved space is not part of another writer
+        assert!(unfilled.fill(&mut String::new(), 1u8).is_err());
+        assert_eq!(buffer, "{\"unfilled\":null,\"name\":null    ,\"ä\":\"ö\"}");
+
+        let mut buffer2 = String::new();
+        let mut object_writer = write_object(&mut buffer2)?;
+        let name = object_writer.reserve_member("name", 8)?;
+        object_writer.end()?;
+        name.fill(&mut buffer2, "äöü")?;
+        assert_eq!(buffer2, "{\"name\":\"äöü\"}");
+        assert_eq!(to_json_string("äöü").len(), 8);
+
+        Ok(())
+    }
+}
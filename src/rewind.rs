@@ -0,0 +1,239 @@
+use crate::{CappedArrayWriter, CappedObjectWriter, JSONArrayWriter, JSONObjectWriter};
+use alloc::string::String;
+
+///
+/// Writers that can discard output after a previous position.
+///
+/// Required for [`JSONObjectWriter::checkpoint`] and [`JSONArrayWriter::checkpoint`].
+///
+/// Positions are only valid as long as no output is removed from the start of the writer,
+/// e.g. by manually flushing it.
+///
+/// Checkpoints are not available for other writers:
+/// ```compile_fail
+/// use json_writer::{write_object, CheckedWriter};
+///
+/// let mut writer = CheckedWriter::new(String::new());
+/// let object_writer = write_object(&mut writer).unwrap();
+/// let checkpoint = object_writer.checkpoint();
+/// ```
+///
+pub trait RewindableWriter: core::fmt::Write {
+    ///
+    /// Returns the number of bytes written so far.
+    ///
+    fn position(&self) -> usize;
+
+    ///
+    /// Discards everything written after `position`.
+    ///
+    fn rewind(&mut self, position: usize);
+}
+
+impl RewindableWriter for String {
+    #[inline(always)]
+    fn position(&self) -> usize {
+        self.len()
+    }
+
+    #[inline(always)]
+    fn rewind(&mut self, position: usize) {
+        self.truncate(position);
+    }
+}
+
+impl<W: RewindableWriter + ?Sized> RewindableWriter for &mut W {
+    #[inline(always)]
+    fn position(&self) -> usize {
+        (**self).position()
+    }
+
+    #[inline(always)]
+    fn rewind(&mut self, position: usize) {
+        (**self).rewind(position)
+    }
+}
+
+///
+/// State of a [`JSONObjectWriter`] or [`JSONArrayWriter`] that can be restored later.
+///
+/// Created with [`JSONObjectWriter::checkpoint`] or [`JSONArrayWriter::checkpoint`],
+/// or the same methods of the capped writers.
+/// Only use it with the writer that created it.
+///
+#[derive(Debug, Copy, Clone)]
+pub struct Checkpoint {
+    position: usize,
+    empty: bool,
+    len: usize,
+    omitted: usize,
+}
+
+impl<W: RewindableWriter> JSONObjectWriter<'_, W> {
+    ///
+    /// Remembers the current state of this object.
+    ///
+    /// Take a checkpoint before writing a member that might fail halfway.
+    /// Call [`JSONObjectWriter::rollback`] to remove the partially written member again.
+    ///
+    /// ```
+    /// use json_writer::write_object;
+    ///
+    /// let mut object_str = String::new();
+    /// let mut object_writer = write_object(&mut object_str).unwrap();
+    /// object_writer.member("a", 1u8).unwrap();
+    ///
+    /// let checkpoint = object_writer.checkpoint();
+    /// let mut items = object_writer.array("items").unwrap();
+    /// items.value(1u8).unwrap();
+    /// drop(items);
+    /// // producing the next item failed
+    /// object_writer.rollback(checkpoint);
+    ///
+    /// object_writer.member("b", 2u8).unwrap();
+    /// object_writer.end().unwrap();
+    /// assert_eq!(&object_str, "{\"a\":1,\"b\":2}");
+    /// ```
+    ///
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            position: self.writer.position(),
+            empty: self.empty,
+            len: 0,
+            omitted: 0,
+        }
+    }
+
+    ///
+    /// Discards everything written to this object since `checkpoint` was taken.
+    ///
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.writer.rewind(checkpoint.position);
+        self.empty = checkpoint.empty;
+    }
+}
+
+impl<W: RewindableWriter> JSONArrayWriter<'_, W> {
+    ///
+    /// Remembers the current state of this array.
+    ///
+    /// Take a checkpoint before writing a value that might fail halfway.
+    /// Call [`JSONArrayWriter::rollback`] to remove the partially written value again.
+    ///
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            position: self.writer.position(),
+            empty: self.empty,
+            len: 0,
+            omitted: 0,
+        }
+    }
+
+    ///
+    /// Discards everything written to this array since `checkpoint` was taken.
+    ///
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.writer.rewind(checkpoint.position);
+        self.empty = checkpoint.empty;
+    }
+}
+
+impl<W: RewindableWriter> CappedObjectWriter<'_, W> {
+    ///
+    /// Remembers the current state of this object, see [`JSONObjectWriter::checkpoint`].
+    ///
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.object.checkpoint()
+    }
+
+    ///
+    /// Discards everything written to this object since `checkpoint` was taken.
+    ///
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.object.rollback(checkpoint);
+    }
+}
+
+impl<W: RewindableWriter> CappedArrayWriter<'_, W> {
+    ///
+    /// Remembers the current state of this array, including the number of written and omitted values.
+    ///
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            len: self.len,
+            omitted: self.omitted,
+            ..self.array.checkpoint()
+        }
+    }
+
+    ///
+    /// Discards everything written to this array since `checkpoint` was taken.
+    ///
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.array.rollback(checkpoint);
+        self.len = checkpoint.len;
+        self.omitted = checkpoint.omitted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{write_array, write_object, ArrayCap};
+
+    fn write_items<W: core::fmt::Write>(
+        array: &mut JSONArrayWriter<'_, W>,
+        items: &[Result<u8, ()>],
+    ) -> Result<(), ()> {
+        for item in items {
+            array.value((*item)?).map_err(|_| ())?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rollback_object() -> Result<(), core::fmt::Error> {
+        let mut buffer = String::new();
+        let mut object_writer = write_object(&mut buffer)?;
+
+        // first member fails
+        let checkpoint = object_writer.checkpoint();
+        let mut items = object_writer.array("first")?;
+        assert!(write_items(&mut items, &[Ok(1), Err(())]).is_err());
+        drop(items);
+        object_writer.rollback(checkpoint);
+
+        // second member succeeds, no leading comma
+        let checkpoint = object_writer.checkpoint();
+        let mut items = object_writer.array("second")?;
+        assert!(write_items(&mut items, &[Ok(1), Ok(2)]).is_ok());
+        items.end()?;
+
+        // rolling back twice to the same checkpoint works
+        object_writer.rollback(checkpoint);
+        object_writer.rollback(checkpoint);
+        object_writer.member("third", 3u8)?;
+        object_writer.end()?;
+
+        assert_eq!(buffer, "{\"third\":3}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rollback_array() -> Result<(), core::fmt::Error> {
+        let mut buffer = String::new();
+        let mut array = write_array(&mut buffer)?.with_array_cap(ArrayCap::new(2));
+        array.value(1u8)?;
+        let checkpoint = array.checkpoint();
+        array.value(2u8)?;
+        array.value(3u8)?;
+        array.rollback(checkpoint);
+        array.value(4u8)?;
+        array.end()?;
+
+        assert_eq!(buffer, "[1,4]");
+
+        Ok(())
+    }
+}
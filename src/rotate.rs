@@ -0,0 +1,346 @@
+use crate::{
+    JSONArrayWriter, JSONObjectWriter, JSONWriterValue, NdjsonWriter, RecordWriter, WriteResult,
+};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+///
+/// Writer for newline-delimited JSON that starts a new file when the current one is full.
+///
+/// Records are written to the files `name.0.jsonl`, `name.1.jsonl`, ... in the given directory.
+/// Numbering continues after the highest number of the existing files (including compressed ones),
+/// so the numbers of the files always follow the order in which they were written.
+/// Every record is serialized before it is written to the current file, a failed record is not written at all.
+/// A file is only created when its first record is written, and files are only rotated between records.
+///
+/// ```no_run
+/// use json_writer::RotatingNdjsonWriter;
+///
+/// let mut writer = RotatingNdjsonWriter::new("logs", "measurements")
+///     .max_bytes(10 * 1024 * 1024)
+///     .max_records(100000);
+/// writer.object(|record| record.member("power", 1.5f64)).unwrap();
+/// writer.finish().unwrap();
+/// ```
+///
+pub struct RotatingNdjsonWriter {
+    directory: PathBuf,
+    name: String,
+    /// Only known after the directory was scanned for existing files.
+    next_index: Option<u64>,
+    max_bytes: u64,
+    max_records: u64,
+    #[cfg(feature = "gzip")]
+    compress_closed_files: bool,
+    /// Serializes one record at a time.
+    record: NdjsonWriter<Vec<u8>>,
+    current: Option<CurrentFile>,
+}
+
+///
+/// File records are currently written to.
+///
+struct CurrentFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    bytes: u64,
+    records: u64,
+}
+
+impl RotatingNdjsonWriter {
+    ///
+    /// Creates a new RotatingNdjsonWriter that writes files named `name.N.jsonl` to `directory`.
+    ///
+    /// Files are not limited in size until [`RotatingNdjsonWriter::max_bytes`] or
+    /// [`RotatingNdjsonWriter::max_records`] is set.
+    ///
+    pub fn new<P: Into<PathBuf>>(directory: P, name: &str) -> RotatingNdjsonWriter {
+        RotatingNdjsonWriter {
+            directory: directory.into(),
+            name: name.to_owned(),
+            next_index: None,
+            max_bytes: u64::MAX,
+            max_records: u64::MAX,
+            #[cfg(feature = "gzip")]
+            compress_closed_files: false,
+            record: NdjsonWriter::new(Vec::new()),
+            current: None,
+        }
+    }
+
+    ///
+    /// Rotates to a new file once the current one has at least `max_bytes` bytes.
+    ///
+    pub fn max_bytes(mut self, max_bytes: u64) -> RotatingNdjsonWriter {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    ///
+    /// Rotates to a new file once the current one has `max_records` records.
+    ///
+    pub fn max_records(mut self, max_records: u64) -> RotatingNdjsonWriter {
+        self.max_records = max_records;
+        self
+    }
+
+    ///
+    /// Enables or disables compressing files with gzip when they are closed.
+    ///
+    /// Compressed files are named `name.N.jsonl.gz`, the uncompressed file is removed.
+    /// Requires the `gzip` feature.
+    ///
+    #[cfg(feature = "gzip")]
+    pub fn compress_closed_files(mut self, enabled: bool) -> RotatingNdjsonWriter {
+        self.compress_closed_files = enabled;
+        self
+    }
+
+    ///
+    /// Returns the path of the file records are currently written to.
+    ///
+    pub fn current_path(&self) -> Option<&Path> {
+        self.current.as_ref().map(|current| current.path.as_path())
+    }
+
+    ///
+    /// Writes a record that is an object.
+    ///
+    /// `f` writes the members, the object is closed afterwards.
+    ///
+    pub fn object<F>(&mut self, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut JSONObjectWriter<'_, RecordWriter<'_>>) -> WriteResult,
+    {
+        self.record.writer_mut().clear();
+        self.record.object(f)?;
+        self.write_record()
+    }
+
+    ///
+    /// Writes a record that is an array.
+    ///
+    /// `f` writes the values, the array is closed afterwards.
+    ///
+    pub fn array<F>(&mut self, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut JSONArrayWriter<'_, RecordWriter<'_>>) -> WriteResult,
+    {
+        self.record.writer_mut().clear();
+        self.record.array(f)?;
+        self.write_record()
+    }
+
+    ///
+    /// Writes a record that is a single value.
+    ///
+    pub fn value<T: JSONWriterValue>(&mut self, value: T) -> std::io::Result<()> {
+        self.record.writer_mut().clear();
+        self.record.value(value)?;
+        self.write_record()
+    }
+
+    ///
+    /// Flushes the current file.
+    ///
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.current {
+            Some(current) => current.writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    ///
+    /// Closes the current file.
+    ///
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.close()
+    }
+
+    ///
+    /// Writes the serialized record to the current file, which is created if there is none.
+    ///
+    fn write_record(&mut self) -> std::io::Result<()> {
+        let current = match self.current.take() {
+            Some(current) => current,
+            None => self.create_next()?,
+        };
+        let current = self.current.insert(current);
+        let record = self.record.writer();
+        current.writer.write_all(record)?;
+        current.bytes += record.len() as u64;
+        current.records += 1;
+        self.rotate_if_full()
+    }
+
+    fn create_next(&mut self) -> std::io::Result<CurrentFile> {
+        let mut next_index = match self.next_index {
+            Some(next_index) => next_index,
+            None => self.scan_directory()?,
+        };
+        loop {
+            let path = self
+                .directory
+                .join(format!("{}.{}.jsonl", self.name, next_index));
+            next_index += 1;
+            self.next_index = Some(next_index);
+            let mut compressed_path = path.clone().into_os_string();
+            compressed_path.push(".gz");
+            if Path::new(&compressed_path).exists() {
+                continue;
+            }
+            // files might be created by someone else in the meantime
+            match File::options().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    return Ok(CurrentFile {
+                        path,
+                        writer: BufWriter::new(file),
+                        bytes: 0,
+                        records: 0,
+                    })
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    ///
+    /// Returns the number after the highest number of the existing files.
+    ///
+    fn scan_directory(&self) -> std::io::Result<u64> {
+        let prefix = format!("{}.", self.name);
+        let mut next_index = 0;
+        for entry in std::fs::read_dir(&self.directory)? {
+            let file_name = entry?.file_name();
+            let index = file_name
+                .to_str()
+                .and_then(|file_name| file_name.strip_prefix(&prefix))
+                .and_then(|rest| {
+                    rest.strip_suffix(".jsonl.gz")
+                        .or_else(|| rest.strip_suffix(".jsonl"))
+                })
+                .and_then(|index| index.parse::<u64>().ok());
+            if let Some(index) = index {
+                next_index = u64::max(next_index, index + 1);
+            }
+        }
+        Ok(next_index)
+    }
+
+    fn rotate_if_full(&mut self) -> std::io::Result<()> {
+        if let Some(current) = &self.current {
+            if current.bytes >= self.max_bytes || current.records >= self.max_records {
+                self.close()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> std::io::Result<()> {
+        let Some(current) = self.current.take() else {
+            return Ok(());
+        };
+        let file = current.writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_data()?;
+        #[cfg(feature = "gzip")]
+        self.compress_if_enabled(&current.path)?;
+        Ok(())
+    }
+
+    ///
+    /// Compresses the closed file at `path` if [`RotatingNdjsonWriter::compress_closed_files`] is enabled.
+    ///
+    #[cfg(feature = "gzip")]
+    fn compress_if_enabled(&self, path: &Path) -> std::io::Result<()> {
+        if self.compress_closed_files {
+            compress_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Compresses the file at `path` to `path.gz` and removes it.
+///
+#[cfg(feature = "gzip")]
+fn compress_file(path: &Path) -> std::io::Result<()> {
+    let mut compressed_path = path.to_owned().into_os_string();
+    compressed_path.push(".gz");
+    let mut input = File::open(path)?;
+    let output = File::create(&compressed_path)?;
+    let mut encoder = flate2::write::GzEncoder::new(output, flate2::Compression::default());
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_data()?;
+    std::fs::remove_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_directory;
+
+    #[test]
+    fn test_rotate() -> std::io::Result<()> {
+        let directory = test_directory("rotate");
+        std::fs::write(directory.join("log.1.jsonl"), "existing\n")?;
+        std::fs::write(directory.join("log.2.jsonl.gz"), "")?;
+        std::fs::write(directory.join("log.x.jsonl"), "")?;
+        std::fs::write(directory.join("other.7.jsonl"), "")?;
+
+        let mut writer = RotatingNdjsonWriter::new(&directory, "log")
+            .max_records(2)
+            .max_bytes(12);
+        // no file is created for a failed record
+        assert!(writer.object(|_| Err(std::fmt::Error)).is_err());
+        assert_eq!(writer.current_path(), None);
+        assert_eq!(std::fs::read_dir(&directory)?.count(), 4);
+        for i in 0..3u8 {
+            writer.value(i)?;
+        }
+        writer.object(|record| record.member("long", "record"))?;
+        // failed records are not written and do not count
+        assert!(writer.array(|_| Err(std::fmt::Error)).is_err());
+        writer.value(4u8)?;
+        assert_eq!(
+            writer.current_path(),
+            Some(directory.join("log.5.jsonl").as_path())
+        );
+        writer.finish()?;
+
+        // numbering continues after the existing files
+        let read =
+            |index: u8| std::fs::read_to_string(directory.join(format!("log.{}.jsonl", index)));
+        assert_eq!(read(1)?, "existing\n");
+        assert_eq!(read(3)?, "0\n1\n");
+        // rotated because the size limit was exceeded
+        assert_eq!(read(4)?, "2\n{\"long\":\"record\"}\n");
+        assert_eq!(read(5)?, "4\n");
+        assert_eq!(std::fs::read_dir(&directory)?.count(), 7);
+
+        std::fs::remove_dir_all(&directory)
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_rotate_compressed() -> std::io::Result<()> {
+        use std::io::Read;
+
+        let directory = test_directory("rotate-compressed");
+        let mut writer = RotatingNdjsonWriter::new(&directory, "log")
+            .max_records(1)
+            .compress_closed_files(true);
+        writer.value("a")?;
+        writer.value("b")?;
+        writer.finish()?;
+
+        let mut json = String::new();
+        let file = File::open(directory.join("log.1.jsonl.gz"))?;
+        flate2::read::GzDecoder::new(file).read_to_string(&mut json)?;
+        assert_eq!(json, "\"b\"\n");
+        assert_eq!(std::fs::read_dir(&directory)?.count(), 2);
+
+        std::fs::remove_dir_all(&directory)
+    }
+}
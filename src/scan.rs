@@ -0,0 +1,290 @@
+//!
+//! Minimal streaming tokenizer for the JSON produced by this crate.
+//!
+//! Used by writers that need to know the structure of the output (e.g. [`crate::CheckedWriter`])
+//! while only seeing the bytes that pass through them.
+//!
+
+use alloc::vec::Vec;
+
+///
+/// Kind of an open container.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Container {
+    Object,
+    Array,
+}
+
+impl Container {
+    ///
+    /// Returns the character that closes this container.
+    ///
+    pub(crate) fn closer(self) -> char {
+        match self {
+            Container::Object => '}',
+            Container::Array => ']',
+        }
+    }
+}
+
+///
+/// What a single byte meant to the tokenizer.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Event {
+    /// Whitespace between tokens.
+    Whitespace,
+    /// '{' or '[' starting a new container.
+    Open(Container),
+    /// '}' or ']' closing the innermost container.
+    Close(Container),
+    /// Opening quote of a string. `key` is true for object keys.
+    StringStart { key: bool },
+    /// Content byte of a string, including escape sequences.
+    StringByte { key: bool },
+    /// Closing quote of a string.
+    StringEnd { key: bool },
+    /// First byte of a number or literal.
+    ScalarStart,
+    /// Any following byte of a number or literal.
+    ScalarByte,
+    /// ':' between key and value.
+    Colon,
+    /// ',' between members.
+    Comma,
+}
+
+impl Event {
+    ///
+    /// Returns true if this byte starts a new value (not a key).
+    ///
+    pub(crate) fn starts_value(self) -> bool {
+        matches!(
+            self,
+            Event::Open(_) | Event::StringStart { key: false } | Event::ScalarStart
+        )
+    }
+}
+
+///
+/// The bytes do not form valid JSON.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct SyntaxError;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Escape {
+    None,
+    Backslash,
+    Hex(u8),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum State {
+    /// Expecting a value.
+    Value,
+    /// Expecting a value or ']' (directly after '[').
+    ValueOrClose,
+    /// Expecting a key (after ',' in an object).
+    Key,
+    /// Expecting a key or '}' (directly after '{').
+    KeyOrClose,
+    /// Expecting ':' after a key.
+    Colon,
+    /// Expecting ',' or the closer of the current container.
+    Next,
+    /// Inside a string.
+    String { key: bool, escape: Escape },
+    /// Inside a number or literal.
+    Scalar,
+}
+
+///
+/// Tokenizer state. Feed it one byte at a time with [`Scanner::push`].
+///
+/// Any number of top-level values may follow each other, which allows using it on
+/// newline-delimited output.
+///
+#[derive(Debug, Clone)]
+pub(crate) struct Scanner {
+    stack: Vec<Container>,
+    state: State,
+}
+
+impl Scanner {
+    pub(crate) fn new() -> Scanner {
+        Scanner {
+            stack: Vec::new(),
+            state: State::Value,
+        }
+    }
+
+    ///
+    /// Returns the currently open containers, outermost first.
+    ///
+    pub(crate) fn stack(&self) -> &[Container] {
+        &self.stack
+    }
+
+    ///
+    /// Returns `Some(key)` if currently inside a string, `None` otherwise.
+    ///
+    pub(crate) fn in_string(&self) -> Option<bool> {
+        match self.state {
+            State::String { key, .. } => Some(key),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Advances the tokenizer by one byte.
+    ///
+    pub(crate) fn push(&mut self, byte: u8) -> Result<Event, SyntaxError> {
+        match self.state {
+            State::String { key, escape } => return self.push_string(key, escape, byte),
+            State::Scalar => {
+                if is_scalar_byte(byte) {
+                    return Ok(Event::ScalarByte);
+                }
+                // the scalar ends at the first byte that is not part of it
+                self.state = State::Next;
+            }
+            _ => {}
+        }
+
+        if is_whitespace(byte) {
+            return Ok(Event::Whitespace);
+        }
+
+        match (self.state, byte) {
+            (State::Value | State::ValueOrClose, _) | (State::Next, _) if self.stack.is_empty() => {
+                self.start_value(byte)
+            }
+            (State::Value | State::ValueOrClose, _) if byte != b']' => self.start_value(byte),
+            (State::ValueOrClose, b']') => self.close(Container::Array),
+            (State::Key | State::KeyOrClose, b'"') => {
+                self.state = State::String {
+                    key: true,
+                    escape: Escape::None,
+                };
+                Ok(Event::StringStart { key: true })
+            }
+            (State::KeyOrClose, b'}') => self.close(Container::Object),
+            (State::Colon, b':') => {
+                self.state = State::Value;
+                Ok(Event::Colon)
+            }
+            (State::Next, b',') => {
+                self.state = match self.stack.last() {
+                    Some(Container::Object) => State::Key,
+                    _ => State::Value,
+                };
+                Ok(Event::Comma)
+            }
+            (State::Next, b'}') => self.close(Container::Object),
+            (State::Next, b']') => self.close(Container::Array),
+            _ => Err(SyntaxError),
+        }
+    }
+
+    fn start_value(&mut self, byte: u8) -> Result<Event, SyntaxError> {
+        match byte {
+            b'{' => {
+                self.stack.push(Container::Object);
+                self.state = State::KeyOrClose;
+                Ok(Event::Open(Container::Object))
+            }
+            b'[' => {
+                self.stack.push(Container::Array);
+                self.state = State::ValueOrClose;
+                Ok(Event::Open(Container::Array))
+            }
+            b'"' => {
+                self.state = State::String {
+                    key: false,
+                    escape: Escape::None,
+                };
+                Ok(Event::StringStart { key: false })
+            }
+            b if is_scalar_byte(b) => {
+                self.state = State::Scalar;
+                Ok(Event::ScalarStart)
+            }
+            _ => Err(SyntaxError),
+        }
+    }
+
+    fn close(&mut self, container: Container) -> Result<Event, SyntaxError> {
+        if self.stack.pop() != Some(container) {
+            return Err(SyntaxError);
+        }
+        self.state = State::Next;
+        Ok(Event::Close(container))
+    }
+
+    fn push_string(&mut self, key: bool, escape: Escape, byte: u8) -> Result<Event, SyntaxError> {
+        let escape = match (escape, byte) {
+            (Escape::None, b'"') => {
+                self.state = if key { State::Colon } else { State::Next };
+                return Ok(Event::StringEnd { key });
+            }
+            (Escape::None, b'\\') => Escape::Backslash,
+            (Escape::None, b) if b < 0x20 => return Err(SyntaxError),
+            (Escape::None, _) => Escape::None,
+            (Escape::Backslash, b'u') => Escape::Hex(4),
+            (Escape::Backslash, b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't') => {
+                Escape::None
+            }
+            (Escape::Hex(remaining), b) if b.is_ascii_hexdigit() => match remaining {
+                1 => Escape::None,
+                _ => Escape::Hex(remaining - 1),
+            },
+            _ => return Err(SyntaxError),
+        };
+        self.state = State::String { key, escape };
+        Ok(Event::StringByte { key })
+    }
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+pub(crate) fn is_scalar_byte(byte: u8) -> bool {
+    matches!(byte, b'0'..=b'9' | b'-' | b'+' | b'.' | b'a'..=b'z' | b'E')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(input: &str) -> Result<Vec<Event>, SyntaxError> {
+        let mut scanner = Scanner::new();
+        input.bytes().map(|b| scanner.push(b)).collect()
+    }
+
+    #[test]
+    fn test_scan_valid() {
+        assert!(scan("{\"a\":[1,true,null,\"\\u00e4\\n\"],\"b\":{}}").is_ok());
+        assert!(scan("[] {} 1 \"x\"\n").is_ok());
+
+        let events = scan("{\"k\":1}").unwrap();
+        assert_eq!(events[0], Event::Open(Container::Object));
+        assert_eq!(events[1], Event::StringStart { key: true });
+        assert_eq!(events[3], Event::StringEnd { key: true });
+        assert_eq!(events[4], Event::Colon);
+        assert_eq!(events[5], Event::ScalarStart);
+        assert_eq!(events[6], Event::Close(Container::Object));
+    }
+
+    #[test]
+    fn test_scan_invalid() {
+        assert_eq!(scan("[1,]"), Err(SyntaxError));
+        assert_eq!(scan("{1:2}"), Err(SyntaxError));
+        assert_eq!(scan("{\"a\"}"), Err(SyntaxError));
+        assert_eq!(scan("[}"), Err(SyntaxError));
+        assert_eq!(scan("\"\\x\""), Err(SyntaxError));
+        assert_eq!(scan("\"\n\""), Err(SyntaxError));
+    }
+}
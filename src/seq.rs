@@ -0,0 +1,171 @@
+use crate::{
+    write_array, write_object, write_value, JSONArrayWriter, JSONObjectWriter, JSONWriterValue,
+    WriteResult,
+};
+use alloc::string::String;
+
+/// Record separator, written before every JSON text.
+const RS: char = '\u{1E}';
+
+///
+/// Writer for JSON text sequences (RFC 7464, `application/json-seq`).
+///
+/// Every record is prefixed with the ASCII record separator (0x1E) and terminated with a line feed.
+/// The line feed is written for all records, so top-level numbers, `true`, `false` and `null`
+/// are always followed by the whitespace the RFC requires to detect truncated values.
+/// Records are buffered and only written once they are complete, failed records are not written at all.
+///
+#[cfg_attr(
+    feature = "std",
+    doc = "Wrap an [`std::io::Write`] in an [`crate::IoWriter`] to write the sequence to a file or socket."
+)]
+///
+/// ```
+/// use json_writer::JsonSeqWriter;
+///
+/// let mut writer = JsonSeqWriter::new(String::new());
+/// writer.object(|record| record.member("id", 1u8)).unwrap();
+/// writer.value(42u8).unwrap();
+///
+/// assert_eq!(writer.records(), 2);
+/// assert_eq!(writer.writer(), "\u{1E}{\"id\":1}\n\u{1E}42\n");
+/// ```
+///
+pub struct JsonSeqWriter<W: core::fmt::Write> {
+    writer: W,
+    buffer: String,
+    records: u64,
+}
+
+impl<W: core::fmt::Write> JsonSeqWriter<W> {
+    ///
+    /// Creates a new JsonSeqWriter that writes records to `writer`.
+    ///
+    pub fn new(writer: W) -> JsonSeqWriter<W> {
+        JsonSeqWriter {
+            writer,
+            buffer: String::new(),
+            records: 0,
+        }
+    }
+
+    ///
+    /// Returns the number of records written successfully.
+    ///
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    ///
+    /// Writes a record that is an object.
+    ///
+    /// `f` writes the members, the object is closed afterwards.
+    ///
+    pub fn object<F>(&mut self, f: F) -> WriteResult
+    where
+        F: FnOnce(&mut JSONObjectWriter<'_, String>) -> WriteResult,
+    {
+        self.record(0, |writer| {
+            let mut object_writer = write_object(writer)?;
+            f(&mut object_writer)?;
+            object_writer.end()
+        })
+    }
+
+    ///
+    /// Writes a record that is an array.
+    ///
+    /// `f` writes the values, the array is closed afterwards.
+    ///
+    pub fn array<F>(&mut self, f: F) -> WriteResult
+    where
+        F: FnOnce(&mut JSONArrayWriter<'_, String>) -> WriteResult,
+    {
+        self.record(0, |writer| {
+            let mut array_writer = write_array(writer)?;
+            f(&mut array_writer)?;
+            array_writer.end()
+        })
+    }
+
+    ///
+    /// Writes a record that is a single value.
+    ///
+    pub fn value<T: JSONWriterValue>(&mut self, value: T) -> WriteResult {
+        self.record(value.size_hint().0, |writer| write_value(writer, value))
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated writer.
+    ///
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated writer.
+    ///
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    ///
+    /// Consumes this writer and returns the encapsulated writer.
+    ///
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    ///
+    /// Writes one framed JSON text. The record is buffered and only written if it was completed
+    /// successfully, so that a failed record is never mistaken for a complete JSON text.
+    ///
+    /// `size_hint` is the expected length of the JSON text without the separators.
+    ///
+    fn record<F>(&mut self, size_hint: usize, f: F) -> WriteResult
+    where
+        F: FnOnce(&mut String) -> WriteResult,
+    {
+        self.buffer.clear();
+        self.buffer.reserve(size_hint + 2);
+        self.buffer.push(RS);
+        f(&mut self.buffer)?;
+        self.buffer.push('\n');
+        self.writer.write_str(&self.buffer)?;
+        self.records += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence() -> Result<(), core::fmt::Error> {
+        let mut writer = JsonSeqWriter::new(String::new());
+        writer.value(1.5f64)?;
+        writer.value("a\nb")?;
+        writer.array(|array| array.value(true))?;
+        writer.object(|_| Ok(()))?;
+        assert_eq!(writer.records(), 4);
+        assert_eq!(
+            writer.into_writer(),
+            "\u{1E}1.5\n\u{1E}\"a\\nb\"\n\u{1E}[true]\n\u{1E}{}\n"
+        );
+
+        // a failing record is not written
+        let mut writer = JsonSeqWriter::new(String::new());
+        assert!(writer
+            .array(|array| {
+                array.value(1u8)?;
+                Err(core::fmt::Error)
+            })
+            .is_err());
+        writer.value(2u8)?;
+        assert_eq!(writer.records(), 1);
+        assert_eq!(writer.writer(), "\u{1E}2\n");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,308 @@
+use crate::{
+    write_array, write_object, IoWriter, JSONArrayWriter, JSONObjectWriter, JSONWriterValue,
+    WriteResult,
+};
+use std::fmt::Write;
+
+///
+/// Optional fields sent before the data of a server-sent event.
+///
+/// ```
+/// use json_writer::SseFields;
+///
+/// let fields = SseFields::new().event("measurement").id("42").retry(1000);
+/// ```
+///
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SseFields<'a> {
+    event: Option<&'a str>,
+    id: Option<&'a str>,
+    retry: Option<u64>,
+}
+
+impl<'a> SseFields<'a> {
+    ///
+    /// Creates fields for an unnamed event without id.
+    ///
+    pub fn new() -> SseFields<'a> {
+        SseFields::default()
+    }
+
+    ///
+    /// Sets the event type (`event:` field).
+    ///
+    pub fn event(mut self, event: &'a str) -> SseFields<'a> {
+        self.event = Some(event);
+        self
+    }
+
+    ///
+    /// Sets the event id (`id:` field).
+    ///
+    pub fn id(mut self, id: &'a str) -> SseFields<'a> {
+        self.id = Some(id);
+        self
+    }
+
+    ///
+    /// Sets the reconnection time in milliseconds (`retry:` field).
+    ///
+    pub fn retry(mut self, milliseconds: u64) -> SseFields<'a> {
+        self.retry = Some(milliseconds);
+        self
+    }
+}
+
+///
+/// Writer for server-sent events (`text/event-stream`) with JSON payloads.
+///
+/// The JSON payload is streamed into the `data:` field. Line breaks in the payload,
+/// e.g. whitespace written directly to the writer, start a new `data:` line.
+/// Like in the event stream itself, "\r\n" is a single line break.
+/// Events are buffered and only written once they are complete, failed events are not written at all.
+/// The encapsulated writer is flushed after every event.
+///
+/// ```
+/// use json_writer::{SseFields, SseWriter};
+///
+/// let mut writer = SseWriter::new(Vec::<u8>::new());
+/// writer
+///     .object(SseFields::new().event("measurement").id("1"), |data| {
+///         data.member("power", 1.5f64)
+///     })
+///     .unwrap();
+///
+/// assert_eq!(
+///     writer.into_writer(),
+///     b"event: measurement\nid: 1\ndata: {\"power\":1.5}\n\n"
+/// );
+/// ```
+///
+pub struct SseWriter<W: std::io::Write> {
+    sink: IoWriter<W>,
+    buffer: String,
+    events: u64,
+}
+
+///
+/// Buffer of the `data:` field of a [`SseWriter`] event.
+///
+pub struct DataWriter<'a> {
+    buffer: &'a mut String,
+    /// True if the last line break was a '\r' that might be followed by '\n'.
+    after_cr: bool,
+}
+
+impl std::fmt::Write for DataWriter<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        if s.is_empty() {
+            return Ok(());
+        }
+        let mut rest = s;
+        if self.after_cr {
+            // "\r\n" split across two writes is a single line break
+            rest = rest.strip_prefix('\n').unwrap_or(rest);
+        }
+        self.after_cr = s.ends_with('\r');
+        while let Some(index) = rest.find(['\n', '\r']) {
+            self.buffer.push_str(&rest[..index]);
+            self.buffer.push_str("\ndata: ");
+            let line_break = if rest[index..].starts_with("\r\n") {
+                2
+            } else {
+                1
+            };
+            rest = &rest[index + line_break..];
+        }
+        self.buffer.push_str(rest);
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write> SseWriter<W> {
+    ///
+    /// Creates a new SseWriter that writes events to `writer`.
+    ///
+    pub fn new(writer: W) -> SseWriter<W> {
+        SseWriter {
+            sink: IoWriter::new(writer),
+            buffer: String::new(),
+            events: 0,
+        }
+    }
+
+    ///
+    /// Returns the number of events written successfully.
+    ///
+    pub fn events(&self) -> u64 {
+        self.events
+    }
+
+    ///
+    /// Writes an event whose data is an object.
+    ///
+    /// `f` writes the members, the object is closed afterwards.
+    ///
+    pub fn object<F>(&mut self, fields: SseFields<'_>, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut JSONObjectWriter<'_, DataWriter<'_>>) -> WriteResult,
+    {
+        self.event(fields, 0, |data| {
+            let mut object_writer = write_object(data)?;
+            f(&mut object_writer)?;
+            object_writer.end()
+        })
+    }
+
+    ///
+    /// Writes an event whose data is an array.
+    ///
+    /// `f` writes the values, the array is closed afterwards.
+    ///
+    pub fn array<F>(&mut self, fields: SseFields<'_>, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut JSONArrayWriter<'_, DataWriter<'_>>) -> WriteResult,
+    {
+        self.event(fields, 0, |data| {
+            let mut array_writer = write_array(data)?;
+            f(&mut array_writer)?;
+            array_writer.end()
+        })
+    }
+
+    ///
+    /// Writes an event whose data is a single value.
+    ///
+    pub fn value<T: JSONWriterValue>(
+        &mut self,
+        fields: SseFields<'_>,
+        value: T,
+    ) -> std::io::Result<()> {
+        self.event(fields, value.size_hint().0, |data| value.write_json(data))
+    }
+
+    ///
+    /// Returns a borrow of the encapsulated writer.
+    ///
+    pub fn writer(&self) -> &W {
+        self.sink.writer()
+    }
+
+    ///
+    /// Returns a mutable borrow of the encapsulated writer.
+    ///
+    pub fn writer_mut(&mut self) -> &mut W {
+        self.sink.writer_mut()
+    }
+
+    ///
+    /// Consumes this writer and returns the encapsulated writer.
+    ///
+    pub fn into_writer(self) -> W {
+        self.sink.into_writer()
+    }
+
+    ///
+    /// Writes an event. `size_hint` is the expected length of the data without the field name.
+    ///
+    fn event<F>(&mut self, fields: SseFields<'_>, size_hint: usize, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut DataWriter<'_>) -> WriteResult,
+    {
+        for value in [fields.event, fields.id].into_iter().flatten() {
+            if value.contains(['\n', '\r', '\0']) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "line break in event field",
+                ));
+            }
+        }
+
+        self.buffer.clear();
+        self.buffer.reserve("data: \n\n".len() + size_hint);
+        write_event(&mut self.buffer, fields, f)
+            .map_err(|_| std::io::Error::other("formatting event data failed"))?;
+        // The event is only sent once it is complete, so that clients never dispatch partial data.
+        self.sink.write_str(&self.buffer).map_err(|_| {
+            self.sink
+                .take_error()
+                .unwrap_or_else(|| std::io::Error::other("writing event failed"))
+        })?;
+        self.events += 1;
+        self.sink.flush()
+    }
+}
+
+///
+/// Writes the fields and the data of an event to `buffer`.
+///
+fn write_event<F>(buffer: &mut String, fields: SseFields<'_>, f: F) -> WriteResult
+where
+    F: FnOnce(&mut DataWriter<'_>) -> WriteResult,
+{
+    if let Some(event) = fields.event {
+        writeln!(buffer, "event: {}", event)?;
+    }
+    if let Some(id) = fields.id {
+        writeln!(buffer, "id: {}", id)?;
+    }
+    if let Some(retry) = fields.retry {
+        writeln!(buffer, "retry: {}", retry)?;
+    }
+    buffer.push_str("data: ");
+    f(&mut DataWriter {
+        buffer,
+        after_cr: false,
+    })?;
+    buffer.push_str("\n\n");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framing() -> std::io::Result<()> {
+        let mut writer = SseWriter::new(Vec::<u8>::new());
+        writer.value(SseFields::new().retry(500), "a\nb")?;
+        writer.array(SseFields::new(), |data| {
+            data.value(1u8)?;
+            data.writer_mut().write_str("\n  ")?;
+            data.value(2u8)
+        })?;
+        assert!(writer.value(SseFields::new().id("1\n2"), 3u8).is_err());
+        // failed events are not sent
+        assert!(writer
+            .object(SseFields::new().event("failed"), |_| Err(std::fmt::Error))
+            .is_err());
+
+        assert_eq!(writer.events(), 2);
+        assert_eq!(
+            std::str::from_utf8(writer.writer()).unwrap(),
+            "retry: 500\ndata: \"a\\nb\"\n\ndata: [1\ndata:   ,2]\n\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_breaks() -> std::io::Result<()> {
+        let mut writer = SseWriter::new(Vec::<u8>::new());
+        writer.array(SseFields::new(), |data| {
+            let writer = data.writer_mut();
+            writer.write_str("\r\n1")?;
+            // "\r\n" split across two writes
+            writer.write_str("\r")?;
+            writer.write_str("")?;
+            writer.write_str("\n2\r\r\n3\n\r")
+        })?;
+
+        assert_eq!(
+            std::str::from_utf8(writer.writer()).unwrap(),
+            "data: [\ndata: 1\ndata: 2\ndata: \ndata: 3\ndata: \ndata: ]\n\n"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+use crate::{JSONArrayWriter, JSONWriterValue};
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_core::Stream;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+///
+/// Stream of [`Bytes`] chunks that contain a JSON array of the values of another stream.
+///
+/// Requires the `stream` feature.
+///
+/// The values are written as soon as they are available, chunks are yielded once they
+/// have at least [`JsonArrayStream::chunk_size`] bytes or when the value stream is pending.
+/// The items are `Result<Bytes, E>`, which can be used directly as the body of a
+/// hyper or axum response (e.g. with `axum::body::Body::from_stream`).
+///
+/// If the value stream yields an error, the error is passed on and the stream ends without closing the array.
+/// The output that was not yielded yet is discarded.
+/// With [`JsonArrayStream::end_array_on_error`], the array is closed instead and the error is discarded.
+///
+/// ```
+/// use json_writer::JsonArrayStream;
+///
+/// let stream = JsonArrayStream::from_iterator(0..1000000u32).chunk_size(16 * 1024);
+/// // let body = axum::body::Body::from_stream(stream);
+/// ```
+///
+pub struct JsonArrayStream<S> {
+    stream: S,
+    buffer: BytesMut,
+    chunk_size: usize,
+    end_array_on_error: bool,
+    empty: bool,
+    done: bool,
+}
+
+impl<S> JsonArrayStream<S> {
+    ///
+    /// Creates a new JsonArrayStream that writes the values of `stream` with a chunk size of 8 KiB.
+    ///
+    pub fn new(stream: S) -> JsonArrayStream<S> {
+        let mut buffer = BytesMut::new();
+        buffer.put_u8(b'[');
+        JsonArrayStream {
+            stream,
+            buffer,
+            chunk_size: 8192,
+            end_array_on_error: false,
+            empty: true,
+            done: false,
+        }
+    }
+
+    ///
+    /// Sets the number of bytes after which a chunk is yielded.
+    ///
+    /// Chunks are larger by up to one value. A chunk size of 0 is treated as 1,
+    /// which yields every value in its own chunk.
+    ///
+    pub fn chunk_size(mut self, bytes: usize) -> JsonArrayStream<S> {
+        // the value stream is only polled while the buffer is smaller than the chunk size
+        self.chunk_size = usize::max(bytes, 1);
+        self
+    }
+
+    ///
+    /// Enables or disables closing the array and ending the stream when the value stream yields an error.
+    ///
+    pub fn end_array_on_error(mut self, enabled: bool) -> JsonArrayStream<S> {
+        self.end_array_on_error = enabled;
+        self
+    }
+}
+
+impl<I: Iterator> JsonArrayStream<IterStream<I>> {
+    ///
+    /// Creates a new JsonArrayStream that writes the values of an iterator.
+    ///
+    pub fn from_iterator<T>(values: T) -> JsonArrayStream<IterStream<I>>
+    where
+        T: IntoIterator<IntoIter = I>,
+    {
+        JsonArrayStream::new(IterStream {
+            iter: values.into_iter(),
+        })
+    }
+}
+
+impl<S, T, E> Stream for JsonArrayStream<S>
+where
+    S: Stream<Item = Result<T, E>> + Unpin,
+    T: JSONWriterValue,
+{
+    type Item = Result<Bytes, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.buffer.is_empty() && !this.done {
+            this.buffer.reserve(this.chunk_size);
+        }
+        while !this.done && this.buffer.len() < this.chunk_size {
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(value))) => {
+                    let mut array = JSONArrayWriter::resume(&mut this.buffer, this.empty);
+                    // BytesMut never returns an error in it's Write implementation.
+                    array.value(value).unwrap();
+                    this.empty = array.suspend();
+                }
+                Poll::Ready(Some(Err(error))) => {
+                    this.done = true;
+                    if !this.end_array_on_error {
+                        this.buffer.clear();
+                        return Poll::Ready(Some(Err(error)));
+                    }
+                    this.buffer.put_u8(b']');
+                }
+                Poll::Ready(None) => {
+                    this.done = true;
+                    this.buffer.put_u8(b']');
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        if !this.buffer.is_empty() {
+            Poll::Ready(Some(Ok(this.buffer.split().freeze())))
+        } else if this.done {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+///
+/// Stream that yields the values of an iterator, created by [`JsonArrayStream::from_iterator`].
+///
+pub struct IterStream<I> {
+    iter: I,
+}
+
+impl<I: Iterator + Unpin> Stream for IterStream<I> {
+    type Item = Result<I::Item, Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().iter.next().map(Ok))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Polls the stream until it ends, ignoring pending polls.
+    fn collect<S: Stream + Unpin>(mut stream: S) -> Vec<S::Item> {
+        let mut cx = Context::from_waker(std::task::Waker::noop());
+        let mut items = Vec::new();
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                Poll::Ready(Some(item)) => items.push(item),
+                Poll::Ready(None) => return items,
+                Poll::Pending => {}
+            }
+        }
+    }
+
+    /// Stream that yields the given polls in order.
+    struct Scripted(VecDeque<Poll<Option<Result<u8, &'static str>>>>);
+
+    impl Stream for Scripted {
+        type Item = Result<u8, &'static str>;
+
+        fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.get_mut().0.pop_front().unwrap_or(Poll::Ready(None))
+        }
+    }
+
+    #[test]
+    fn test_chunks() {
+        let numbers: Vec<u32> = (0..1000).collect();
+        let chunks = collect(JsonArrayStream::from_iterator(&numbers).chunk_size(64));
+        let (last, full) = chunks.split_last().unwrap();
+        assert!(full.iter().all(|chunk| chunk.as_ref().unwrap().len() >= 64));
+        assert!(last.as_ref().unwrap().len() < 64 + 5);
+
+        let json: Vec<u8> = chunks
+            .into_iter()
+            .flat_map(|chunk| chunk.unwrap())
+            .collect();
+        assert_eq!(json, crate::to_json_string(&numbers).as_bytes());
+
+        let chunks = collect(JsonArrayStream::from_iterator(Vec::<u8>::new()));
+        assert_eq!(chunks, vec![Ok(Bytes::from("[]"))]);
+    }
+
+    #[test]
+    fn test_chunk_size_zero() {
+        let chunks = collect(JsonArrayStream::from_iterator([1u8, 2]).chunk_size(0));
+        assert_eq!(
+            chunks,
+            vec![
+                Ok(Bytes::from("[")),
+                Ok(Bytes::from("1")),
+                Ok(Bytes::from(",2")),
+                Ok(Bytes::from("]"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pending_and_errors() {
+        let polls = || {
+            Scripted(VecDeque::from(vec![
+                Poll::Ready(Some(Ok(1))),
+                Poll::Pending,
+                Poll::Ready(Some(Ok(2))),
+                Poll::Ready(Some(Err("failed"))),
+                Poll::Ready(Some(Ok(3))),
+            ]))
+        };
+
+        // buffered output is yielded when the value stream is pending
+        let chunks = collect(JsonArrayStream::new(polls()));
+        assert_eq!(chunks, vec![Ok(Bytes::from("[1")), Err("failed")]);
+
+        let chunks = collect(JsonArrayStream::new(polls()).end_array_on_error(true));
+        assert_eq!(chunks, vec![Ok(Bytes::from("[1")), Ok(Bytes::from(",2]"))]);
+    }
+}
@@ -0,0 +1,144 @@
+///
+/// Writer that writes everything to two writers.
+///
+/// Output is written to the primary writer first. By default an error of either writer fails the write.
+/// With [`Tee::ignore_secondary_errors`], the secondary writer is skipped after its first error
+/// and only errors of the primary writer are returned.
+///
+/// ```
+/// use json_writer::{write_object, Fnv1a, HashingWriter, Tee};
+///
+/// let mut writer = Tee::new(String::new(), HashingWriter::new(Fnv1a::new()));
+/// let mut object_writer = write_object(&mut writer).unwrap();
+/// object_writer.member("id", 1u8).unwrap();
+/// object_writer.end().unwrap();
+///
+/// let (json, hashing_writer) = writer.into_parts();
+/// assert_eq!(json, "{\"id\":1}");
+/// let hash = hashing_writer.finalize();
+/// ```
+///
+pub struct Tee<A: core::fmt::Write, B: core::fmt::Write> {
+    primary: A,
+    secondary: B,
+    ignore_secondary_errors: bool,
+    secondary_failed: bool,
+}
+
+impl<A: core::fmt::Write, B: core::fmt::Write> Tee<A, B> {
+    ///
+    /// Creates a new Tee that writes to `primary` and `secondary`.
+    ///
+    pub fn new(primary: A, secondary: B) -> Tee<A, B> {
+        Tee {
+            primary,
+            secondary,
+            ignore_secondary_errors: false,
+            secondary_failed: false,
+        }
+    }
+
+    ///
+    /// Enables or disables ignoring errors of the secondary writer.
+    ///
+    pub fn ignore_secondary_errors(mut self, enabled: bool) -> Tee<A, B> {
+        self.ignore_secondary_errors = enabled;
+        self
+    }
+
+    ///
+    /// Returns true if writing to the secondary writer failed.
+    ///
+    /// The output of the secondary writer is incomplete in this case.
+    ///
+    pub fn secondary_failed(&self) -> bool {
+        self.secondary_failed
+    }
+
+    ///
+    /// Returns a borrow of the primary writer.
+    ///
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    ///
+    /// Returns a mutable borrow of the primary writer.
+    ///
+    pub fn primary_mut(&mut self) -> &mut A {
+        &mut self.primary
+    }
+
+    ///
+    /// Returns a borrow of the secondary writer.
+    ///
+    pub fn secondary(&self) -> &B {
+        &self.secondary
+    }
+
+    ///
+    /// Returns a mutable borrow of the secondary writer.
+    ///
+    pub fn secondary_mut(&mut self) -> &mut B {
+        &mut self.secondary
+    }
+
+    ///
+    /// Consumes this writer and returns the primary and secondary writer.
+    ///
+    pub fn into_parts(self) -> (A, B) {
+        (self.primary, self.secondary)
+    }
+}
+
+impl<A: core::fmt::Write, B: core::fmt::Write> core::fmt::Write for Tee<A, B> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.primary.write_str(s)?;
+        if self.secondary_failed {
+            return if self.ignore_secondary_errors {
+                Ok(())
+            } else {
+                Err(core::fmt::Error)
+            };
+        }
+        if self.secondary.write_str(s).is_err() {
+            self.secondary_failed = true;
+            if !self.ignore_secondary_errors {
+                return Err(core::fmt::Error);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{write_array, BudgetWriter};
+
+    #[test]
+    fn test_tee() -> Result<(), core::fmt::Error> {
+        let numbers: [u32; 4] = [1, 20, 300, 4000];
+
+        let mut writer = Tee::new(String::new(), BudgetWriter::new(String::new(), 8))
+            .ignore_secondary_errors(true);
+        let mut array = write_array(&mut writer)?;
+        for number in numbers {
+            array.value(number)?;
+        }
+        array.end()?;
+        assert!(writer.secondary_failed());
+        let (primary, secondary) = writer.into_parts();
+        assert_eq!(primary, "[1,20,300,4000]");
+        assert_eq!(secondary.writer(), "[1,20]");
+
+        let mut writer = Tee::new(String::new(), BudgetWriter::new(String::new(), 8));
+        let mut array = write_array(&mut writer)?;
+        assert!(numbers.iter().try_for_each(|n| array.value(*n)).is_err());
+        drop(array);
+        assert!(writer.secondary_failed());
+        assert_eq!(writer.primary(), "[1,20,300]");
+
+        Ok(())
+    }
+}